@@ -0,0 +1,84 @@
+//! Mede o tempo de `tokenizar` em um arquivo gerado com ~50 mil linhas,
+//! comparando com o lexer anterior, que percorria um `Vec<char>`.
+//!
+//! Execute com `cargo bench --bench lexer`.
+
+// Cópia do lexer sobre `Vec<char>`, mantida apenas como referência de medida
+#[path = "lexer/vec_char.rs"]
+mod vec_char;
+
+use pbrlang::lexer::tokenizar;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const LINHAS: usize = 50_000;
+const REPETICOES: u32 = 10;
+
+// Linhas geradas por função em `gerar_programa`
+const LINHAS_POR_FUNCAO: usize = 12;
+
+/// Gera um programa PBRLang com identificadores, textos, números e comentários
+fn gerar_programa() -> String {
+    let mut codigo = String::new();
+    
+    for i in 0..LINHAS / LINHAS_POR_FUNCAO {
+        codigo.push_str(&format!(
+            "/// Calcula o valor número {i}\n\
+             faça calcular_média_{i}(preço: número, quantidade: inteiro) -> número {{\n\
+             \x20   // Comentário comum\n\
+             \x20   pense total_{i} = preço * quantidade + 1_000 + 0xFF + 3.14e-2;\n\
+             \x20   se total_{i} >= 100 {{\n\
+             \x20       mostre \"Total acima do limite: ${{total_{i}}} reais\\n\";\n\
+             \x20   }} senão {{\n\
+             \x20       mostre \"Ação concluída para o item {i}\";\n\
+             \x20   }}\n\
+             \x20   volte total_{i};\n\
+             }}\n\n"
+        ));
+    }
+    
+    codigo
+}
+
+/// Executa `lexer` `REPETICOES` vezes, imprime o melhor tempo e a média e
+/// retorna o melhor
+fn medir(nome: &str, codigo: &str, lexer: impl Fn(&str) -> usize) -> Duration {
+    // Aquecimento
+    let quantidade = lexer(codigo);
+    
+    let mut melhor = Duration::MAX;
+    let mut total = Duration::ZERO;
+    for _ in 0..REPETICOES {
+        let inicio = Instant::now();
+        black_box(lexer(black_box(codigo)));
+        let tempo = inicio.elapsed();
+        melhor = melhor.min(tempo);
+        total += tempo;
+    }
+    
+    println!(
+        "{:<9} {} tokens — melhor {:.2?}, média {:.2?} ({:.1} MB/s)",
+        nome,
+        quantidade,
+        melhor,
+        total / REPETICOES,
+        codigo.len() as f64 / melhor.as_secs_f64() / 1e6,
+    );
+    
+    melhor
+}
+
+fn main() {
+    let codigo = gerar_programa();
+    
+    println!("{} linhas, {} bytes", codigo.lines().count(), codigo.len());
+    
+    let antes = medir("Vec<char>", &codigo, |codigo| {
+        vec_char::tokenizar(codigo).expect("o programa gerado deve ser válido").len()
+    });
+    let depois = medir("&str", &codigo, |codigo| {
+        tokenizar(codigo).expect("o programa gerado deve ser válido").len()
+    });
+    
+    println!("&str é {:.2}x mais rápido", antes.as_secs_f64() / depois.as_secs_f64());
+}
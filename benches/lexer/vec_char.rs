@@ -0,0 +1,776 @@
+//! O lexer anterior ao atual, que coleta a entrada em um `Vec<char>`.
+//! Mantido sem alterações, exceto a remoção das funções que o benchmark não
+//! usa, para comparar com `pbrlang::lexer`.
+
+#![allow(clippy::explicit_counter_loop)]
+
+use anyhow::Result;
+use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
+use unicode_xid::UnicodeXID;
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Token {
+    // Palavras-chave
+    Faca,           // faça
+    Mostre,         // mostre
+    Volte,          // volte
+    Se,             // se
+    Senao,          // senão
+    ParaCada,       // para cada
+    De,             // de
+    Ate,            // até
+    Modelo,         // modelo
+    QuandoDerErro,  // quando der erro
+    FalharCom,      // falhar com
+    Pense,          // pense
+    Verdadeiro,     // verdadeiro
+    Falso,          // falso
+    Nada,           // nada
+    
+    // Tipos
+    TipoTexto,      // texto
+    TipoNumero,     // número
+    TipoLogico,     // lógico
+    TipoInteiro,    // inteiro
+    
+    // Identificadores e literais
+    Identificador(String),
+    InteiroLiteral(i64),
+    NumeroLiteral(f64),
+    TextoLiteral(String),
+    
+    // Texto com interpolação: "Olá, ${nome}!" vira
+    // TextoInicio("Olá, "), Identificador("nome"), TextoFim("!").
+    // Entre duas interpolações aparece TextoMeio.
+    TextoInicio(String),  // "...${
+    TextoMeio(String),    // }...${
+    TextoFim(String),     // }..."
+    
+    // Operadores
+    Mais,           // +
+    Menos,          // -
+    Vezes,          // *
+    Dividido,       // /
+    Igual,          // =
+    IgualA,         // é igual a
+    Diferente,      // !=
+    Maior,          // >
+    Menor,          // <
+    MaiorIgual,     // >=
+    MenorIgual,     // <=
+    
+    // Símbolos
+    AbreChave,      // {
+    FechaChave,     // }
+    AbreParentese,  // (
+    FechaParentese, // )
+    Virgula,        // ,
+    DoisPontos,     // :
+    PontoVirgula,   // ;
+    
+    // Comentário de documentação (`/// texto`), sem as barras
+    ComentarioDoc(String),
+    
+    // Fim de arquivo
+    EOF,
+}
+
+/// Token acompanhado da sua localização no código fonte.
+///
+/// `inicio` e `fim` são deslocamentos em bytes (intervalo semiaberto), de modo
+/// que `&codigo[inicio..fim]` é exatamente o trecho que originou o token.
+/// `linha` e `coluna` começam em 1 e apontam para o primeiro caractere.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenComPosicao {
+    pub token: Token,
+    pub inicio: usize,
+    pub fim: usize,
+    pub linha: usize,
+    pub coluna: usize,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum LexerError {
+    #[error("Caractere inesperado '{caractere}' na linha {linha}, coluna {coluna}")]
+    CaractereInesperado {
+        caractere: char,
+        inicio: usize,
+        fim: usize,
+        linha: usize,
+        coluna: usize,
+    },
+    
+    #[error("String não terminada (iniciada na linha {linha}, coluna {coluna})")]
+    StringNaoTerminada {
+        inicio: usize,
+        fim: usize,
+        linha: usize,
+        coluna: usize,
+    },
+    
+    #[error("Sequência de escape inválida '{sequencia}' na linha {linha}, coluna {coluna}")]
+    EscapeInvalido {
+        sequencia: String,
+        inicio: usize,
+        fim: usize,
+        linha: usize,
+        coluna: usize,
+    },
+    
+    #[error("Comentário de bloco não terminado (iniciado na linha {linha}, coluna {coluna})")]
+    ComentarioNaoTerminado {
+        inicio: usize,
+        fim: usize,
+        linha: usize,
+        coluna: usize,
+    },
+    
+    #[error("Erro no formato do número '{texto}' na linha {linha}, coluna {coluna}")]
+    ErroNumero {
+        texto: String,
+        inicio: usize,
+        fim: usize,
+        linha: usize,
+        coluna: usize,
+    },
+}
+
+pub struct Lexer {
+    entrada: Vec<char>,
+    posicao: usize,
+    // Deslocamento em bytes correspondente a `posicao`
+    byte: usize,
+    linha: usize,
+    coluna: usize,
+    // Interpolações `${ ... }` abertas, da mais externa para a mais interna
+    interpolacoes: Vec<Interpolacao>,
+}
+
+/// Estado de uma interpolação aberta dentro de um texto
+struct Interpolacao {
+    // Onde começa o texto (a aspas inicial), para mensagens de erro
+    texto: Marca,
+    // Quantas chaves `{` foram abertas dentro da expressão
+    profundidade: usize,
+}
+
+/// Posição salva no início de um token, usada para montar spans e erros
+#[derive(Clone, Copy)]
+struct Marca {
+    posicao: usize,
+    byte: usize,
+    linha: usize,
+    coluna: usize,
+}
+
+impl Lexer {
+    pub fn new(entrada: &str) -> Self {
+        Lexer {
+            entrada: entrada.chars().collect(),
+            posicao: 0,
+            byte: 0,
+            linha: 1,
+            coluna: 1,
+            interpolacoes: Vec::new(),
+        }
+    }
+    
+    fn avancar(&mut self) {
+        if self.posicao < self.entrada.len() {
+            let c = self.entrada[self.posicao];
+            if c == '\n' {
+                self.linha += 1;
+                self.coluna = 1;
+            } else {
+                self.coluna += 1;
+            }
+            self.byte += c.len_utf8();
+            self.posicao += 1;
+        }
+    }
+    
+    fn marca(&self) -> Marca {
+        Marca {
+            posicao: self.posicao,
+            byte: self.byte,
+            linha: self.linha,
+            coluna: self.coluna,
+        }
+    }
+    
+    fn caractere_atual(&self) -> Option<char> {
+        if self.posicao < self.entrada.len() {
+            Some(self.entrada[self.posicao])
+        } else {
+            None
+        }
+    }
+    
+    fn proximo_caractere(&self) -> Option<char> {
+        if self.posicao + 1 < self.entrada.len() {
+            Some(self.entrada[self.posicao + 1])
+        } else {
+            None
+        }
+    }
+    
+    fn pular_espacos(&mut self) {
+        while let Some(c) = self.caractere_atual() {
+            if c.is_whitespace() {
+                self.avancar();
+            } else {
+                break;
+            }
+        }
+    }
+    
+    fn comeca_com(&self, prefixo: &str) -> bool {
+        let mut posicao = self.posicao;
+        for esperado in prefixo.chars() {
+            if self.entrada.get(posicao) != Some(&esperado) {
+                return false;
+            }
+            posicao += 1;
+        }
+        true
+    }
+    
+    /// Verifica se a posição atual inicia um comentário de documentação.
+    /// Assim como em Rust, `////` é um comentário comum.
+    fn comeca_comentario_doc(&self) -> bool {
+        self.comeca_com("///") && !self.comeca_com("////")
+    }
+    
+    /// Pula espaços em branco, comentários de linha (`//`) e comentários de
+    /// bloco (`/* ... */`, que podem ser aninhados). Comentários de
+    /// documentação não são pulados, pois viram tokens.
+    fn pular_espacos_e_comentarios(&mut self) -> Result<(), LexerError> {
+        loop {
+            self.pular_espacos();
+            
+            if self.comeca_com("//") && !self.comeca_comentario_doc() {
+                // Pular até o final da linha
+                while let Some(c) = self.caractere_atual() {
+                    self.avancar();
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else if self.comeca_com("/*") {
+                self.pular_comentario_bloco()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+    
+    fn pular_comentario_bloco(&mut self) -> Result<(), LexerError> {
+        let marca = self.marca();
+        let mut profundidade = 0;
+        
+        loop {
+            if self.comeca_com("/*") {
+                profundidade += 1;
+                self.avancar();
+                self.avancar();
+            } else if self.comeca_com("*/") {
+                profundidade -= 1;
+                self.avancar();
+                self.avancar();
+                if profundidade == 0 {
+                    return Ok(());
+                }
+            } else if self.caractere_atual().is_some() {
+                self.avancar();
+            } else {
+                return Err(LexerError::ComentarioNaoTerminado {
+                    inicio: marca.byte,
+                    fim: self.byte,
+                    linha: marca.linha,
+                    coluna: marca.coluna,
+                });
+            }
+        }
+    }
+    
+    fn ler_comentario_doc(&mut self) -> Token {
+        let mut linha = String::new();
+        
+        while let Some(c) = self.caractere_atual() {
+            if c == '\n' {
+                break;
+            }
+            linha.push(c);
+            self.avancar();
+        }
+        
+        Token::ComentarioDoc(texto_documentacao(&linha).to_string())
+    }
+    
+    fn restaurar(&mut self, marca: Marca) {
+        self.posicao = marca.posicao;
+        self.byte = marca.byte;
+        self.linha = marca.linha;
+        self.coluna = marca.coluna;
+    }
+    
+    /// Tenta ler, em sequência, as palavras informadas (separadas por espaços),
+    /// para reconhecer palavras-chave compostas como `para cada`. Se alguma
+    /// palavra não corresponder, a posição é restaurada e nada é consumido.
+    fn tentar_palavras(&mut self, palavras: &[&str]) -> bool {
+        let marca = self.marca();
+        
+        for palavra in palavras {
+            self.pular_espacos();
+            
+            let inicia_identificador = self.caractere_atual()
+                .is_some_and(eh_inicio_identificador);
+            if !inicia_identificador || self.ler_identificador() != *palavra {
+                self.restaurar(marca);
+                return false;
+            }
+        }
+        
+        true
+    }
+    
+    fn ler_identificador(&mut self) -> String {
+        let mut id = String::new();
+        
+        while let Some(c) = self.caractere_atual() {
+            if eh_continuacao_identificador(c) {
+                id.push(c);
+                self.avancar();
+            } else {
+                break;
+            }
+        }
+        
+        normalizar_identificador(&id)
+    }
+    
+    /// Lê dígitos da base informada, aceitando `_` como separador entre eles
+    fn ler_digitos(&mut self, bruto: &mut String, base: u32) {
+        while let Some(c) = self.caractere_atual() {
+            if c.is_digit(base) || c == '_' {
+                bruto.push(c);
+                self.avancar();
+            } else {
+                break;
+            }
+        }
+    }
+    
+    /// Lê um literal numérico: inteiros (`42`, `1_000_000`, `0xFF`, `0b1010`,
+    /// `0o17`) ou decimais (`3.14`, `1.5e-3`, `2E10`)
+    fn ler_numero(&mut self) -> Result<Token, LexerError> {
+        let marca = self.marca();
+        let mut bruto = String::new();
+        
+        let base = match (self.caractere_atual(), self.proximo_caractere()) {
+            (Some('0'), Some('x' | 'X')) => 16,
+            (Some('0'), Some('b' | 'B')) => 2,
+            (Some('0'), Some('o' | 'O')) => 8,
+            _ => 10,
+        };
+        
+        let mut decimal = false;
+        
+        if base != 10 {
+            // Pular o prefixo
+            self.avancar();
+            self.avancar();
+            self.ler_digitos(&mut bruto, base);
+        } else {
+            self.ler_digitos(&mut bruto, 10);
+            
+            // Parte fracionária: o ponto só pertence ao número se for seguido
+            // de um dígito
+            if self.caractere_atual() == Some('.')
+                && self.proximo_caractere().is_some_and(|c| c.is_ascii_digit())
+            {
+                decimal = true;
+                bruto.push('.');
+                self.avancar();
+                self.ler_digitos(&mut bruto, 10);
+            }
+            
+            // Expoente
+            if let Some(e @ ('e' | 'E')) = self.caractere_atual() {
+                let depois = self.proximo_caractere();
+                let depois_sinal = self.entrada.get(self.posicao + 2).copied();
+                let tem_expoente = match depois {
+                    Some(c) if c.is_ascii_digit() => true,
+                    Some('+' | '-') => depois_sinal.is_some_and(|c| c.is_ascii_digit()),
+                    _ => false,
+                };
+                
+                if tem_expoente {
+                    decimal = true;
+                    bruto.push(e);
+                    self.avancar();
+                    if let Some(sinal @ ('+' | '-')) = self.caractere_atual() {
+                        bruto.push(sinal);
+                        self.avancar();
+                    }
+                    self.ler_digitos(&mut bruto, 10);
+                }
+            }
+        }
+        
+        // Um número não pode ser seguido diretamente de letras (ex: `12abc`, `0xFG`)
+        while let Some(c) = self.caractere_atual() {
+            if eh_continuacao_identificador(c) {
+                bruto.push(c);
+                self.avancar();
+            } else {
+                break;
+            }
+        }
+        
+        let erro = |lexer: &Self| LexerError::ErroNumero {
+            texto: lexer.entrada[marca.posicao..lexer.posicao].iter().collect(),
+            inicio: marca.byte,
+            fim: lexer.byte,
+            linha: marca.linha,
+            coluna: marca.coluna,
+        };
+        
+        // O separador `_` só é permitido entre dígitos
+        let digitos_validos = !bruto.is_empty()
+            && !bruto.starts_with('_')
+            && bruto.char_indices().all(|(i, c)| {
+                c != '_' || bruto[i + 1..].chars().next().is_some_and(|d| d == '_' || d.is_digit(base))
+            });
+        if !digitos_validos {
+            return Err(erro(self));
+        }
+        
+        let limpo = bruto.replace('_', "");
+        
+        if decimal {
+            match limpo.parse::<f64>() {
+                Ok(valor) if valor.is_finite() => Ok(Token::NumeroLiteral(valor)),
+                _ => Err(erro(self)),
+            }
+        } else {
+            i64::from_str_radix(&limpo, base)
+                .map(Token::InteiroLiteral)
+                .map_err(|_| erro(self))
+        }
+    }
+    
+    /// Lê um trecho de texto até a aspas final ou até o início de uma
+    /// interpolação (`${`). Deve ser chamado logo após a aspas inicial ou
+    /// após a `}` que fecha uma interpolação.
+    fn ler_parte_texto(&mut self, marca: Marca, inicio_texto: bool) -> Result<Token, LexerError> {
+        let marca_texto = if inicio_texto {
+            marca
+        } else {
+            // A interpolação terminou; os erros continuam apontando para a
+            // aspas inicial do texto
+            self.interpolacoes.pop().map_or(marca, |i| i.texto)
+        };
+        let mut texto = String::new();
+        
+        while let Some(c) = self.caractere_atual() {
+            match c {
+                '"' => {
+                    self.avancar(); // Pular a aspas final
+                    return Ok(if inicio_texto {
+                        Token::TextoLiteral(texto)
+                    } else {
+                        Token::TextoFim(texto)
+                    });
+                },
+                '$' if self.proximo_caractere() == Some('{') => {
+                    self.avancar();
+                    self.avancar();
+                    self.interpolacoes.push(Interpolacao {
+                        texto: marca_texto,
+                        profundidade: 0,
+                    });
+                    return Ok(if inicio_texto {
+                        Token::TextoInicio(texto)
+                    } else {
+                        Token::TextoMeio(texto)
+                    });
+                },
+                '\\' => texto.push(self.ler_escape()?),
+                _ => {
+                    texto.push(c);
+                    self.avancar();
+                }
+            }
+        }
+        
+        Err(LexerError::StringNaoTerminada {
+            inicio: marca_texto.byte,
+            fim: self.byte,
+            linha: marca_texto.linha,
+            coluna: marca_texto.coluna,
+        })
+    }
+    
+    /// Decodifica uma sequência de escape iniciada em `\`
+    fn ler_escape(&mut self) -> Result<char, LexerError> {
+        let marca = self.marca();
+        self.avancar(); // Pular a barra invertida
+        
+        let c = match self.caractere_atual() {
+            Some(c) => c,
+            None => return Err(self.escape_invalido(marca)),
+        };
+        self.avancar();
+        
+        match c {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '\'' => Ok('\''),
+            '$' => Ok('$'),
+            'u' => {
+                // Formato \u{XXXX}, com 1 a 6 dígitos hexadecimais
+                if self.caractere_atual() != Some('{') {
+                    return Err(self.escape_invalido(marca));
+                }
+                self.avancar();
+                
+                let mut hex = String::new();
+                while let Some(c) = self.caractere_atual() {
+                    if c == '}' || !c.is_ascii_hexdigit() {
+                        break;
+                    }
+                    hex.push(c);
+                    self.avancar();
+                }
+                
+                if self.caractere_atual() != Some('}') || hex.is_empty() || hex.len() > 6 {
+                    return Err(self.escape_invalido(marca));
+                }
+                self.avancar();
+                
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.escape_invalido(marca))
+            },
+            _ => Err(self.escape_invalido(marca)),
+        }
+    }
+    
+    fn escape_invalido(&self, marca: Marca) -> LexerError {
+        let sequencia: String = self.entrada[marca.posicao..self.posicao].iter().collect();
+        LexerError::EscapeInvalido {
+            sequencia,
+            inicio: marca.byte,
+            fim: self.byte,
+            linha: marca.linha,
+            coluna: marca.coluna,
+        }
+    }
+    
+    /// Lê o próximo token junto com a sua posição no código fonte
+    pub fn proximo_token(&mut self) -> Result<TokenComPosicao, LexerError> {
+        self.pular_espacos_e_comentarios()?;
+        
+        let marca = self.marca();
+        let token = self.ler_token(marca)?;
+        
+        Ok(TokenComPosicao {
+            token,
+            inicio: marca.byte,
+            fim: self.byte,
+            linha: marca.linha,
+            coluna: marca.coluna,
+        })
+    }
+    
+    fn ler_token(&mut self, marca: Marca) -> Result<Token, LexerError> {
+        match self.caractere_atual() {
+            None => match self.interpolacoes.last() {
+                // O arquivo terminou dentro de um `${ ... }`
+                Some(interpolacao) => Err(LexerError::StringNaoTerminada {
+                    inicio: interpolacao.texto.byte,
+                    fim: self.byte,
+                    linha: interpolacao.texto.linha,
+                    coluna: interpolacao.texto.coluna,
+                }),
+                None => Ok(Token::EOF),
+            },
+            
+            Some(c) => {
+                match c {
+                    // Operadores e símbolos
+                    '{' => {
+                        self.avancar();
+                        if let Some(interpolacao) = self.interpolacoes.last_mut() {
+                            interpolacao.profundidade += 1;
+                        }
+                        Ok(Token::AbreChave)
+                    },
+                    '}' => {
+                        self.avancar();
+                        match self.interpolacoes.last_mut() {
+                            // Fim de uma interpolação: continuar lendo o texto
+                            Some(interpolacao) if interpolacao.profundidade == 0 => {
+                                self.ler_parte_texto(marca, false)
+                            },
+                            Some(interpolacao) => {
+                                interpolacao.profundidade -= 1;
+                                Ok(Token::FechaChave)
+                            },
+                            None => Ok(Token::FechaChave),
+                        }
+                    },
+                    '(' => { self.avancar(); Ok(Token::AbreParentese) },
+                    ')' => { self.avancar(); Ok(Token::FechaParentese) },
+                    ',' => { self.avancar(); Ok(Token::Virgula) },
+                    ':' => { self.avancar(); Ok(Token::DoisPontos) },
+                    ';' => { self.avancar(); Ok(Token::PontoVirgula) },
+                    '+' => { self.avancar(); Ok(Token::Mais) },
+                    '-' => { self.avancar(); Ok(Token::Menos) },
+                    '*' => { self.avancar(); Ok(Token::Vezes) },
+                    '/' if self.comeca_comentario_doc() => Ok(self.ler_comentario_doc()),
+                    '/' => { self.avancar(); Ok(Token::Dividido) },
+                    '=' => { self.avancar(); Ok(Token::Igual) },
+                    '>' => {
+                        self.avancar();
+                        if let Some('=') = self.caractere_atual() {
+                            self.avancar();
+                            Ok(Token::MaiorIgual)
+                        } else {
+                            Ok(Token::Maior)
+                        }
+                    },
+                    '<' => {
+                        self.avancar();
+                        if let Some('=') = self.caractere_atual() {
+                            self.avancar();
+                            Ok(Token::MenorIgual)
+                        } else {
+                            Ok(Token::Menor)
+                        }
+                    },
+                    '!' => {
+                        self.avancar();
+                        if let Some('=') = self.caractere_atual() {
+                            self.avancar();
+                            Ok(Token::Diferente)
+                        } else {
+                            Err(self.caractere_inesperado('!', marca))
+                        }
+                    },
+                    
+                    // Literais de texto
+                    '"' => {
+                        self.avancar(); // Pular a aspas inicial
+                        self.ler_parte_texto(marca, true)
+                    },
+                    
+                    // Números
+                    '0'..='9' => self.ler_numero(),
+                    
+                    // Identificadores e palavras-chave
+                    c if eh_inicio_identificador(c) => {
+                        let id = self.ler_identificador();
+                        
+                        match id.as_str() {
+                            "faça" => Ok(Token::Faca),
+                            "mostre" => Ok(Token::Mostre),
+                            "volte" => Ok(Token::Volte),
+                            "se" => Ok(Token::Se),
+                            "senão" => Ok(Token::Senao),
+                            "para" if self.tentar_palavras(&["cada"]) => Ok(Token::ParaCada),
+                            "de" => Ok(Token::De),
+                            "até" | "ate" => Ok(Token::Ate),
+                            "modelo" => Ok(Token::Modelo),
+                            "quando" if self.tentar_palavras(&["der", "erro"]) => Ok(Token::QuandoDerErro),
+                            "falhar" if self.tentar_palavras(&["com"]) => Ok(Token::FalharCom),
+                            "pense" => Ok(Token::Pense),
+                            "verdadeiro" => Ok(Token::Verdadeiro),
+                            "falso" => Ok(Token::Falso),
+                            "nada" => Ok(Token::Nada),
+                            
+                            // Tipos
+                            "texto" => Ok(Token::TipoTexto),
+                            "número" | "numero" => Ok(Token::TipoNumero),
+                            "lógico" | "logico" => Ok(Token::TipoLogico),
+                            "inteiro" => Ok(Token::TipoInteiro),
+                            
+                            // Comparações especiais
+                            "é" if self.tentar_palavras(&["igual", "a"]) => Ok(Token::IgualA),
+                            
+                            // Identificador normal
+                            _ => Ok(Token::Identificador(id)),
+                        }
+                    },
+                    
+                    // Qualquer outro caractere é um erro
+                    _ => Err(self.caractere_inesperado(c, marca)),
+                }
+            }
+        }
+    }
+    
+    fn caractere_inesperado(&self, caractere: char, marca: Marca) -> LexerError {
+        LexerError::CaractereInesperado {
+            caractere,
+            inicio: marca.byte,
+            fim: marca.byte + caractere.len_utf8(),
+            linha: marca.linha,
+            coluna: marca.coluna,
+        }
+    }
+}
+
+/// Um identificador começa com um caractere `XID_Start` (letras de qualquer
+/// alfabeto, incluindo as acentuadas) ou `_`. A gramática pest segue a mesma
+/// regra.
+pub fn eh_inicio_identificador(c: char) -> bool {
+    c.is_xid_start() || c == '_'
+}
+
+/// Caracteres `XID_Continue`: letras, dígitos, `_` e marcas combinantes
+pub fn eh_continuacao_identificador(c: char) -> bool {
+    c.is_xid_continue()
+}
+
+/// Normaliza um identificador na forma NFC, para que `número` digitado com
+/// acento combinante (`u` + U+0301) seja igual à forma pré-composta
+pub fn normalizar_identificador(nome: &str) -> String {
+    nome.nfc().collect()
+}
+
+/// Extrai o texto de uma linha de comentário de documentação, removendo
+/// o `///` inicial, um espaço opcional e os espaços do final
+pub fn texto_documentacao(linha: &str) -> &str {
+    let linha = linha.trim();
+    let texto = linha.strip_prefix("///").unwrap_or(linha);
+    texto.strip_prefix(' ').unwrap_or(texto).trim_end()
+}
+
+/// Converte o código fonte em uma lista de tokens com suas posições.
+/// O último token é sempre `Token::EOF`.
+pub fn tokenizar(codigo: &str) -> Result<Vec<TokenComPosicao>> {
+    let mut lexer = Lexer::new(codigo);
+    let mut tokens = Vec::new();
+    
+    loop {
+        let token = lexer.proximo_token().map_err(anyhow::Error::new)?;
+        let fim = token.token == Token::EOF;
+        tokens.push(token);
+        
+        if fim {
+            break;
+        }
+    }
+    
+    Ok(tokens)
+}
+
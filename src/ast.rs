@@ -5,9 +5,18 @@
 pub enum Tipo {
     Texto,
     Numero,
+    Inteiro,
     Logico,
     Void,
     Personalizado(String),
+    // `lista de T`
+    Lista(Box<Tipo>),
+    // `mapa de K para V`; sem `para`, as chaves são textos
+    Mapa(Box<Tipo>, Box<Tipo>),
+    // Modelo com parâmetros de tipo: `Caixa<T>`
+    Generico(String, Vec<Tipo>),
+    // `função(número) -> texto`; sem retorno, a função devolve `nada`
+    Funcao(Vec<Tipo>, Option<Box<Tipo>>),
     // Para representar tipos opcionais (equivalente a Option<T> em Rust)
     Opcional(Box<Tipo>),
 }
@@ -16,6 +25,9 @@ pub enum Tipo {
 pub enum Expressao {
     // Literais
     TextoLiteral(String),
+    // Texto com interpolação: "Olá, ${nome}!"
+    TextoInterpolado(Vec<ParteTexto>),
+    InteiroLiteral(i64),
     NumeroLiteral(f64),
     LogicoLiteral(bool),
     Nada,
@@ -28,15 +40,59 @@ pub enum Expressao {
         nome: String,
         argumentos: Vec<Expressao>,
     },
+    // Chamada de um método: `texto.maiusculas(x)`
+    ChamadaMetodo {
+        objeto: Box<Expressao>,
+        metodo: String,
+        argumentos: Vec<Expressao>,
+    },
+    // Chamada de uma função obtida de outra expressão: `obter_funcao()(x)`
+    ChamadaExpressao {
+        funcao: Box<Expressao>,
+        argumentos: Vec<Expressao>,
+    },
     Operacao {
         operador: Operador,
         esquerda: Box<Expressao>,
         direita: Box<Expressao>,
     },
+    // Comparações encadeadas: `a < b <= c`, com um operador entre cada par
+    // de operandos vizinhos; cada operando é avaliado uma só vez
+    Comparacao {
+        operandos: Vec<Expressao>,
+        operadores: Vec<Operador>,
+    },
     Atribuicao {
         nome: String,
         valor: Box<Expressao>,
     },
+    AtribuicaoMembro {
+        objeto: Box<Expressao>,
+        membro: String,
+        valor: Box<Expressao>,
+    },
+    
+    // Instância de um modelo com os valores padrão (`nova Pessoa()`)
+    NovaInstancia {
+        modelo: String,
+    },
+    
+    // Instância de um modelo com valores para os campos
+    // (`Pessoa { nome: "Ana", idade: 30 }`); os demais ficam com o padrão
+    LiteralModelo {
+        modelo: String,
+        campos: Vec<(String, Expressao)>,
+    },
+    
+    // O valor que recebe a chamada de um método (`eu.nome`)
+    Eu,
+    
+    // Função sem nome: `faça(x: número) -> número { volte x * 2 }`
+    FuncaoAnonima {
+        parametros: Vec<(String, Tipo)>,
+        tipo_retorno: Option<Tipo>,
+        corpo: Box<Declaracao>,
+    },
     
     // Acesso a membros (como em objetos/structs)
     AcessoMembro {
@@ -44,10 +100,33 @@ pub enum Expressao {
         membro: String,
     },
     
-    // Operador unário (negação)
+    // Acesso por índice ou chave (`lista[0]`, `mapa["nome"]`)
+    Indice {
+        alvo: Box<Expressao>,
+        indice: Box<Expressao>,
+    },
+    
+    // Fatia de uma lista ou texto (`lista[1..3]`); os limites são opcionais
+    Fatia {
+        alvo: Box<Expressao>,
+        inicio: Option<Box<Expressao>>,
+        fim: Option<Box<Expressao>>,
+    },
+    
+    // Operadores unários: negação lógica (`não x`) e sinal (`-x`)
     Negacao {
         expressao: Box<Expressao>,
     },
+    Negativo {
+        expressao: Box<Expressao>,
+    },
+}
+
+/// Parte de um texto interpolado: um trecho fixo ou uma expressão `${...}`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParteTexto {
+    Texto(String),
+    Expressao(Expressao),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -91,10 +170,35 @@ pub enum Declaracao {
         bloco_senao: Option<Box<Declaracao>>,
     },
     
+    // `escolha`: cada caso tem um ou mais padrões e o bloco executado
+    // quando um deles combina com o valor escolhido
+    Escolha {
+        valor: Expressao,
+        casos: Vec<(Vec<Padrao>, Declaracao)>,
+        padrao: Option<Box<Declaracao>>,
+    },
+    
+    // `para cada i de 1 até 10 passo 2`; sem passo, conta de um em um
     ParaCada {
         variavel: String,
         inicio: Expressao,
         fim: Expressao,
+        passo: Option<Expressao>,
+        corpo: Box<Declaracao>,
+    },
+    
+    // `para cada item em lista`
+    ParaCadaEm {
+        variavel: String,
+        colecao: Expressao,
+        corpo: Box<Declaracao>,
+    },
+    
+    // `para cada (chave, valor) em mapa`
+    ParaCadaPar {
+        chave: String,
+        valor: String,
+        colecao: Expressao,
         corpo: Box<Declaracao>,
     },
     
@@ -121,11 +225,26 @@ pub enum Declaracao {
     
     // Funções
     Funcao {
+        nome: String,
+        genericos: Vec<String>,  // parâmetros de tipo: `faça primeiro<T>`
+        parametros: Vec<(String, Tipo)>,
+        tipo_retorno: Option<Tipo>,
+        corpo: Box<Declaracao>,
+        publico: bool,
+        // Texto dos comentários `///` que precedem a função
+        documentacao: Option<String>,
+    },
+    
+    // Método de um modelo (`faça Pessoa.apresentar(eu)`); os parâmetros
+    // não incluem o `eu`
+    Metodo {
+        modelo: String,
         nome: String,
         parametros: Vec<(String, Tipo)>,
         tipo_retorno: Option<Tipo>,
         corpo: Box<Declaracao>,
         publico: bool,
+        documentacao: Option<String>,
     },
     
     Retorno(Option<Expressao>),
@@ -133,11 +252,32 @@ pub enum Declaracao {
     // Mostrar no console
     Mostrar(Expressao),
     
-    // Declaração de modelo (struct)
+    // Declaração de modelo (struct), com os métodos declarados dentro dele
+    // e os contratos que ele cumpre (`modelo Circulo cumpre Forma`)
     Modelo {
         nome: String,
+        genericos: Vec<String>,
         campos: Vec<(String, Tipo, bool)>,  // (nome, tipo, publico)
+        metodos: Vec<Declaracao>,
+        contratos: Vec<String>,
         publico: bool,
+        documentacao: Option<String>,
+    },
+    
+    // Declaração de enumeração (`tipo Forma { Circulo(raio: número) }`)
+    Enumeracao {
+        nome: String,
+        variantes: Vec<(String, Vec<(String, Tipo)>)>,  // (nome, campos)
+        publico: bool,
+        documentacao: Option<String>,
+    },
+    
+    // Declaração de contrato (trait): os métodos que um modelo precisa ter
+    Contrato {
+        nome: String,
+        metodos: Vec<Assinatura>,
+        publico: bool,
+        documentacao: Option<String>,
     },
     
     Modulo {
@@ -148,9 +288,33 @@ pub enum Declaracao {
     Importar {
         caminho: Vec<String>,
     },
+    
+    // Declaração que não pôde ser analisada (ver `analisar_com_recuperacao`)
+    Erro,
+}
+
+/// Padrão de um `caso` em `escolha`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Padrao {
+    // Valor comparado por igualdade: `caso 1`, `caso "a"`, `caso Vazio`
+    Valor(Expressao),
+    // Variante de enumeração, com um nome para cada campo: `caso Circulo(r)`
+    Variante {
+        nome: String,
+        campos: Vec<String>,
+    },
+}
+
+/// Assinatura de um método em um contrato: `faça area(eu) -> número`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assinatura {
+    pub nome: String,
+    pub parametros: Vec<(String, Tipo)>,  // sem o `eu`
+    pub tipo_retorno: Option<Tipo>,
+    pub documentacao: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Programa {
     pub declaracoes: Vec<Declaracao>,
 }
@@ -132,7 +132,6 @@ impl Manifesto {
         // Implementação simplificada para analisar um formato chave-valor
         let mut manifesto = Manifesto::novo("temp", "0.1.0");
         let mut estado_atual = Estado::Normal;
-        let mut chave_temp = String::new();
         
         for linha in conteudo.lines() {
             let linha = linha.trim();
@@ -150,7 +149,6 @@ impl Manifesto {
                         if valor == "{" {
                             // Início de uma seção de dependências
                             estado_atual = Estado::Dependencias;
-                            chave_temp = chave;
                         } else {
                             // Valor único
                             Self::definir_valor(&mut manifesto, &chave, valor)?;
@@ -186,23 +184,19 @@ impl Manifesto {
             "principal" => self.principal = valor.trim_matches('"').to_string(),
             "licenca" => self.licenca = valor.trim_matches('"').to_string(),
             "repositorio" => self.repositorio = valor.trim_matches('"').to_string(),
-            "autores" => {
-                if valor.starts_with('[') && valor.ends_with(']') {
-                    let autores = valor[1..valor.len()-1]
-                        .split(',')
-                        .map(|a| a.trim().trim_matches('"').to_string())
-                        .collect();
-                    self.autores = autores;
-                }
+            "autores" if valor.starts_with('[') && valor.ends_with(']') => {
+                let autores = valor[1..valor.len()-1]
+                    .split(',')
+                    .map(|a| a.trim().trim_matches('"').to_string())
+                    .collect();
+                self.autores = autores;
             },
-            "palavras_chave" => {
-                if valor.starts_with('[') && valor.ends_with(']') {
-                    let palavras = valor[1..valor.len()-1]
-                        .split(',')
-                        .map(|p| p.trim().trim_matches('"').to_string())
-                        .collect();
-                    self.palavras_chave = palavras;
-                }
+            "palavras_chave" if valor.starts_with('[') && valor.ends_with(']') => {
+                let palavras = valor[1..valor.len()-1]
+                    .split(',')
+                    .map(|p| p.trim().trim_matches('"').to_string())
+                    .collect();
+                self.palavras_chave = palavras;
             },
             _ => {
                 // Ignorar chaves desconhecidas
@@ -260,7 +254,7 @@ impl GerenciadorPacotes {
         let versao = versao.unwrap_or("latest");
         
         // Baixar pacote (simulado)
-        println!("Baixando pacote {} versão {}", nome, versao);
+        println!("Baixando pacote {} versão {} para {}", nome, versao, self.cache_dir.join(nome).display());
         
         // Instalar pacote (simulado)
         println!("Instalando pacote {} versão {}", nome, versao);
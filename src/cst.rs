@@ -0,0 +1,86 @@
+//! Árvore Sintática Concreta (CST) para PBRLang
+//! Guarda cada token junto com os espaços e comentários ao seu redor, de modo
+//! que imprimir a árvore reproduz o código fonte byte a byte. Serve de base
+//! para ferramentas que precisam preservar o layout do usuário, como
+//! formatadores e refatorações.
+
+use crate::lexer::{Lexer, LexerError, TipoTrivia, Token, TokenComPosicao, Trivia};
+use std::fmt;
+
+/// Token com a sua trivia.
+///
+/// A trivia de um token é dividida como em outros compiladores: a que vem
+/// depois dele, até a primeira quebra de linha (inclusive), é `trivia_depois`;
+/// o restante pertence ao próximo token, como `trivia_antes`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenConcreto<'a> {
+    pub trivia_antes: Vec<Trivia<'a>>,
+    pub token: TokenComPosicao<'a>,
+    // Trecho original do token, sem normalização nem decodificação de escapes
+    pub texto: &'a str,
+    pub trivia_depois: Vec<Trivia<'a>>,
+}
+
+/// Sequência completa de tokens de um arquivo, terminada por `Token::EOF`.
+/// A trivia no fim do arquivo fica em `trivia_antes` do `EOF`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArvoreConcreta<'a> {
+    pub tokens: Vec<TokenConcreto<'a>>,
+}
+
+impl fmt::Display for TokenConcreto<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for trivia in &self.trivia_antes {
+            f.write_str(trivia.texto)?;
+        }
+        f.write_str(self.texto)?;
+        for trivia in &self.trivia_depois {
+            f.write_str(trivia.texto)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ArvoreConcreta<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            write!(f, "{}", token)?;
+        }
+        Ok(())
+    }
+}
+
+/// Constrói a árvore concreta do código. `analisar_concreto(codigo)?.to_string()`
+/// é sempre igual a `codigo`.
+pub fn analisar_concreto(codigo: &str) -> Result<ArvoreConcreta<'_>, LexerError> {
+    let mut lexer = Lexer::new(codigo);
+    let mut tokens: Vec<TokenConcreto> = Vec::new();
+    
+    loop {
+        let (mut trivia, token) = lexer.proximo_token_com_trivia()?;
+        
+        // A trivia até a primeira quebra de linha pertence ao token anterior
+        if let Some(anterior) = tokens.last_mut() {
+            let fim_da_linha = trivia.iter()
+                .position(|t| t.tipo == TipoTrivia::QuebraLinha)
+                .map_or(trivia.len(), |i| i + 1);
+            let restante = trivia.split_off(fim_da_linha);
+            anterior.trivia_depois = trivia;
+            trivia = restante;
+        }
+        
+        let fim = token.token == Token::EOF;
+        tokens.push(TokenConcreto {
+            trivia_antes: trivia,
+            texto: &codigo[token.inicio..token.fim],
+            token,
+            trivia_depois: Vec::new(),
+        });
+        
+        if fim {
+            break;
+        }
+    }
+    
+    Ok(ArvoreConcreta { tokens })
+}
@@ -1,8 +1,16 @@
 use anyhow::Result;
+use std::borrow::Cow;
+use std::fmt;
 use thiserror::Error;
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+use unicode_xid::UnicodeXID;
 
+/// Token produzido pelo lexer. Identificadores e textos apontam diretamente
+/// para o código fonte (`Cow::Borrowed`) e só alocam quando o conteúdo
+/// precisa ser transformado (escapes ou normalização Unicode).
 #[derive(Debug, Clone, PartialEq)]
-pub enum Token {
+#[allow(clippy::upper_case_acronyms)]
+pub enum Token<'a> {
     // Palavras-chave
     Faca,           // faça
     Mostre,         // mostre
@@ -24,93 +32,325 @@ pub enum Token {
     TipoTexto,      // texto
     TipoNumero,     // número
     TipoLogico,     // lógico
+    TipoInteiro,    // inteiro
     
     // Identificadores e literais
-    Identificador(String),
+    Identificador(Cow<'a, str>),
+    InteiroLiteral(i64),
     NumeroLiteral(f64),
-    TextoLiteral(String),
+    TextoLiteral(Cow<'a, str>),
+    
+    // Texto com interpolação: "Olá, ${nome}!" vira
+    // TextoInicio("Olá, "), Identificador("nome"), TextoFim("!").
+    // Entre duas interpolações aparece TextoMeio.
+    TextoInicio(Cow<'a, str>),  // "...${
+    TextoMeio(Cow<'a, str>),    // }...${
+    TextoFim(Cow<'a, str>),     // }..."
     
     // Operadores
     Mais,           // +
     Menos,          // -
     Vezes,          // *
     Dividido,       // /
+    Resto,          // % ou resto
     Igual,          // =
+    IgualIgual,     // ==
     IgualA,         // é igual a
     Diferente,      // !=
     Maior,          // >
     Menor,          // <
     MaiorIgual,     // >=
     MenorIgual,     // <=
+    Nao,            // ! ou não
+    E,              // e
+    Ou,             // ou
+    Em,             // em
+    Seta,           // ->
+    
+    // Atribuições compostas
+    MaisIgual,      // +=
+    MenosIgual,     // -=
+    VezesIgual,     // *=
+    DivididoIgual,  // /=
+    RestoIgual,     // %=
     
     // Símbolos
     AbreChave,      // {
     FechaChave,     // }
     AbreParentese,  // (
     FechaParentese, // )
+    AbreColchete,   // [
+    FechaColchete,  // ]
     Virgula,        // ,
+    Ponto,          // .
+    Intervalo,      // ..
     DoisPontos,     // :
     PontoVirgula,   // ;
     
+    // Comentário de documentação (`/// texto`), sem as barras
+    ComentarioDoc(&'a str),
+    
+    // Trecho inválido, emitido no lugar do token quando o lexer se recupera
+    // de um erro (ver `tokenizar_com_recuperacao`)
+    Erro,
+    
     // Fim de arquivo
     EOF,
 }
 
-#[derive(Debug, Error)]
+impl fmt::Display for Token<'_> {
+    /// Escreve o token como ele aparece no código, para mensagens de erro
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let texto = match self {
+            Token::Identificador(nome) => return f.write_str(nome),
+            Token::InteiroLiteral(valor) => return write!(f, "{}", valor),
+            Token::NumeroLiteral(valor) => return write!(f, "{}", valor),
+            Token::TextoLiteral(texto) => return write!(f, "\"{}\"", texto),
+            Token::TextoInicio(texto) => return write!(f, "\"{}${{", texto),
+            Token::TextoMeio(texto) => return write!(f, "}}{}${{", texto),
+            Token::TextoFim(texto) => return write!(f, "}}{}\"", texto),
+            Token::ComentarioDoc(texto) => return write!(f, "///{}", texto),
+            Token::Faca => "faça",
+            Token::Mostre => "mostre",
+            Token::Volte => "volte",
+            Token::Se => "se",
+            Token::Senao => "senão",
+            Token::ParaCada => "para cada",
+            Token::De => "de",
+            Token::Ate => "até",
+            Token::Modelo => "modelo",
+            Token::QuandoDerErro => "quando der erro",
+            Token::FalharCom => "falhar com",
+            Token::Pense => "pense",
+            Token::Verdadeiro => "verdadeiro",
+            Token::Falso => "falso",
+            Token::Nada => "nada",
+            Token::TipoTexto => "texto",
+            Token::TipoNumero => "número",
+            Token::TipoLogico => "lógico",
+            Token::TipoInteiro => "inteiro",
+            Token::Mais => "+",
+            Token::Menos => "-",
+            Token::Vezes => "*",
+            Token::Dividido => "/",
+            Token::Resto => "%",
+            Token::Igual => "=",
+            Token::IgualIgual => "==",
+            Token::IgualA => "é igual a",
+            Token::Diferente => "!=",
+            Token::Maior => ">",
+            Token::Menor => "<",
+            Token::MaiorIgual => ">=",
+            Token::MenorIgual => "<=",
+            Token::Nao => "não",
+            Token::E => "e",
+            Token::Ou => "ou",
+            Token::Em => "em",
+            Token::Seta => "->",
+            Token::MaisIgual => "+=",
+            Token::MenosIgual => "-=",
+            Token::VezesIgual => "*=",
+            Token::DivididoIgual => "/=",
+            Token::RestoIgual => "%=",
+            Token::AbreChave => "{",
+            Token::FechaChave => "}",
+            Token::AbreParentese => "(",
+            Token::FechaParentese => ")",
+            Token::AbreColchete => "[",
+            Token::FechaColchete => "]",
+            Token::Virgula => ",",
+            Token::Ponto => ".",
+            Token::Intervalo => "..",
+            Token::DoisPontos => ":",
+            Token::PontoVirgula => ";",
+            Token::Erro => "?",
+            Token::EOF => "",
+        };
+        f.write_str(texto)
+    }
+}
+
+/// Trecho do código fonte que não vira token: espaços, quebras de linha e
+/// comentários comuns. Só é preservado pela árvore concreta (`crate::cst`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trivia<'a> {
+    pub tipo: TipoTrivia,
+    pub texto: &'a str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TipoTrivia {
+    Espaco,           // espaços e tabulações
+    QuebraLinha,      // \n ou \r\n
+    ComentarioLinha,  // // ...
+    ComentarioBloco,  // /* ... */
+}
+
+/// Token acompanhado da sua localização no código fonte.
+///
+/// `inicio` e `fim` são deslocamentos em bytes (intervalo semiaberto), de modo
+/// que `&codigo[inicio..fim]` é exatamente o trecho que originou o token.
+/// `linha` e `coluna` começam em 1 e apontam para o primeiro caractere.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenComPosicao<'a> {
+    pub token: Token<'a>,
+    pub inicio: usize,
+    pub fim: usize,
+    pub linha: usize,
+    pub coluna: usize,
+}
+
+#[derive(Debug, Error, PartialEq)]
 pub enum LexerError {
-    #[error("Caractere inesperado: {0}")]
-    CaractereInesperado(char),
+    #[error("Caractere inesperado '{caractere}' na linha {linha}, coluna {coluna}")]
+    CaractereInesperado {
+        caractere: char,
+        inicio: usize,
+        fim: usize,
+        linha: usize,
+        coluna: usize,
+    },
     
-    #[error("String não terminada")]
-    StringNaoTerminada,
+    #[error("String não terminada (iniciada na linha {linha}, coluna {coluna})")]
+    StringNaoTerminada {
+        inicio: usize,
+        fim: usize,
+        linha: usize,
+        coluna: usize,
+    },
     
-    #[error("Erro no formato do número: {0}")]
-    ErroNumero(String),
+    #[error("Sequência de escape inválida '{sequencia}' na linha {linha}, coluna {coluna}")]
+    EscapeInvalido {
+        sequencia: String,
+        inicio: usize,
+        fim: usize,
+        linha: usize,
+        coluna: usize,
+    },
+    
+    #[error("Comentário de bloco não terminado (iniciado na linha {linha}, coluna {coluna})")]
+    ComentarioNaoTerminado {
+        inicio: usize,
+        fim: usize,
+        linha: usize,
+        coluna: usize,
+    },
+    
+    #[error("Erro no formato do número '{texto}' na linha {linha}, coluna {coluna}")]
+    ErroNumero {
+        texto: String,
+        inicio: usize,
+        fim: usize,
+        linha: usize,
+        coluna: usize,
+    },
+}
+
+impl LexerError {
+    /// Intervalo em bytes do trecho com problema
+    pub fn intervalo(&self) -> std::ops::Range<usize> {
+        match self {
+            LexerError::CaractereInesperado { inicio, fim, .. }
+            | LexerError::StringNaoTerminada { inicio, fim, .. }
+            | LexerError::EscapeInvalido { inicio, fim, .. }
+            | LexerError::ComentarioNaoTerminado { inicio, fim, .. }
+            | LexerError::ErroNumero { inicio, fim, .. } => *inicio..*fim,
+        }
+    }
+    
+    /// Linha e coluna onde o trecho com problema começa
+    pub fn linha_coluna(&self) -> (usize, usize) {
+        match self {
+            LexerError::CaractereInesperado { linha, coluna, .. }
+            | LexerError::StringNaoTerminada { linha, coluna, .. }
+            | LexerError::EscapeInvalido { linha, coluna, .. }
+            | LexerError::ComentarioNaoTerminado { linha, coluna, .. }
+            | LexerError::ErroNumero { linha, coluna, .. } => (*linha, *coluna),
+        }
+    }
+}
+
+/// Lexer que percorre o código fonte diretamente, por deslocamentos em bytes
+pub struct Lexer<'a> {
+    entrada: &'a str,
+    // Deslocamento em bytes do caractere atual
+    byte: usize,
+    linha: usize,
+    coluna: usize,
+    // Interpolações `${ ... }` abertas, da mais externa para a mais interna
+    interpolacoes: Vec<Interpolacao>,
+    // Quando ativo, escapes inválidos não interrompem o texto e os erros são
+    // acumulados em `erros`
+    recuperando: bool,
+    erros: Vec<LexerError>,
 }
 
-pub struct Lexer {
-    entrada: Vec<char>,
-    posicao: usize,
+/// Estado de uma interpolação aberta dentro de um texto
+struct Interpolacao {
+    // Onde começa o texto (a aspas inicial), para mensagens de erro
+    texto: Marca,
+    // Quantas chaves `{` foram abertas dentro da expressão
+    profundidade: usize,
+}
+
+/// Posição salva no início de um token, usada para montar spans e erros
+#[derive(Clone, Copy)]
+struct Marca {
+    byte: usize,
     linha: usize,
     coluna: usize,
 }
 
-impl Lexer {
-    pub fn new(entrada: &str) -> Self {
+impl<'a> Lexer<'a> {
+    pub fn new(entrada: &'a str) -> Self {
         Lexer {
-            entrada: entrada.chars().collect(),
-            posicao: 0,
+            entrada,
+            byte: 0,
             linha: 1,
             coluna: 1,
+            interpolacoes: Vec::new(),
+            recuperando: false,
+            erros: Vec::new(),
         }
     }
     
+    /// Trecho ainda não lido do código fonte
+    fn restante(&self) -> &'a str {
+        &self.entrada[self.byte..]
+    }
+    
     fn avancar(&mut self) {
-        if self.posicao < self.entrada.len() {
-            if self.entrada[self.posicao] == '\n' {
+        if let Some(c) = self.caractere_atual() {
+            if c == '\n' {
                 self.linha += 1;
                 self.coluna = 1;
             } else {
                 self.coluna += 1;
             }
-            self.posicao += 1;
+            self.byte += c.len_utf8();
         }
     }
     
-    fn caractere_atual(&self) -> Option<char> {
-        if self.posicao < self.entrada.len() {
-            Some(self.entrada[self.posicao])
-        } else {
-            None
+    /// Avança sobre um trecho ASCII sem quebras de linha, como `/*` ou `0x`
+    fn avancar_ascii(&mut self, bytes: usize) {
+        self.byte += bytes;
+        self.coluna += bytes;
+    }
+    
+    fn marca(&self) -> Marca {
+        Marca {
+            byte: self.byte,
+            linha: self.linha,
+            coluna: self.coluna,
         }
     }
     
+    fn caractere_atual(&self) -> Option<char> {
+        self.restante().chars().next()
+    }
+    
     fn proximo_caractere(&self) -> Option<char> {
-        if self.posicao + 1 < self.entrada.len() {
-            Some(self.entrada[self.posicao + 1])
-        } else {
-            None
-        }
+        self.restante().chars().nth(1)
     }
     
     fn pular_espacos(&mut self) {
@@ -123,190 +363,560 @@ impl Lexer {
         }
     }
     
-    fn ler_identificador(&mut self) -> String {
-        let mut id = String::new();
-        
-        while let Some(c) = self.caractere_atual() {
-            if c.is_alphanumeric() || c == '_' {
-                id.push(c);
-                self.avancar();
+    fn comeca_com(&self, prefixo: &str) -> bool {
+        self.restante().starts_with(prefixo)
+    }
+    
+    /// Verifica se a posição atual inicia um comentário de documentação.
+    /// Assim como em Rust, `////` é um comentário comum.
+    fn comeca_comentario_doc(&self) -> bool {
+        self.comeca_com("///") && !self.comeca_com("////")
+    }
+    
+    /// Pula espaços em branco, comentários de linha (`//`) e comentários de
+    /// bloco (`/* ... */`, que podem ser aninhados). Comentários de
+    /// documentação não são pulados, pois viram tokens.
+    fn pular_espacos_e_comentarios(&mut self) -> Result<(), LexerError> {
+        loop {
+            self.pular_espacos();
+            
+            if self.comeca_com("//") && !self.comeca_comentario_doc() {
+                // Pular até o final da linha (a quebra de linha fica para
+                // `pular_espacos`)
+                self.ler_ate_fim_da_linha();
+            } else if self.comeca_com("/*") {
+                self.pular_comentario_bloco()?;
             } else {
-                break;
+                return Ok(());
             }
         }
-        
-        id
     }
     
-    fn ler_numero(&mut self) -> Result<f64, LexerError> {
-        let mut num = String::new();
-        let mut tem_ponto = false;
+    /// Lê um único trecho de trivia (o mesmo que `pular_espacos_e_comentarios`
+    /// descarta), ou `None` se a posição atual já inicia um token
+    fn ler_trivia(&mut self) -> Result<Option<Trivia<'a>>, LexerError> {
+        let inicio = self.byte;
         
-        while let Some(c) = self.caractere_atual() {
-            if c.is_digit(10) {
-                num.push(c);
+        let tipo = match self.caractere_atual() {
+            Some('\n') => {
+                self.avancar();
+                TipoTrivia::QuebraLinha
+            },
+            Some('\r') if self.proximo_caractere() == Some('\n') => {
+                self.avancar();
                 self.avancar();
-            } else if c == '.' && !tem_ponto {
-                num.push(c);
-                tem_ponto = true;
+                TipoTrivia::QuebraLinha
+            },
+            Some(c) if c.is_whitespace() => {
+                self.avancar_enquanto(|c| c.is_whitespace() && !matches!(c, '\n' | '\r'));
+                // `\r` sozinho também é espaço
+                if self.byte == inicio {
+                    self.avancar();
+                }
+                TipoTrivia::Espaco
+            },
+            _ if self.comeca_com("//") && !self.comeca_comentario_doc() => {
+                self.ler_ate_fim_da_linha();
+                TipoTrivia::ComentarioLinha
+            },
+            _ if self.comeca_com("/*") => {
+                self.pular_comentario_bloco()?;
+                TipoTrivia::ComentarioBloco
+            },
+            _ => return Ok(None),
+        };
+        
+        Ok(Some(Trivia {
+            tipo,
+            texto: &self.entrada[inicio..self.byte],
+        }))
+    }
+    
+    fn pular_comentario_bloco(&mut self) -> Result<(), LexerError> {
+        let marca = self.marca();
+        let mut profundidade = 0;
+        
+        loop {
+            if self.comeca_com("/*") {
+                profundidade += 1;
+                self.avancar_ascii(2);
+            } else if self.comeca_com("*/") {
+                profundidade -= 1;
+                self.avancar_ascii(2);
+                if profundidade == 0 {
+                    return Ok(());
+                }
+            } else if self.caractere_atual().is_some() {
                 self.avancar();
             } else {
+                return Err(LexerError::ComentarioNaoTerminado {
+                    inicio: marca.byte,
+                    fim: self.byte,
+                    linha: marca.linha,
+                    coluna: marca.coluna,
+                });
+            }
+        }
+    }
+    
+    /// Consome o restante da linha atual, sem a quebra de linha
+    fn ler_ate_fim_da_linha(&mut self) -> &'a str {
+        let restante = self.restante();
+        let linha = &restante[..restante.find('\n').unwrap_or(restante.len())];
+        self.byte += linha.len();
+        self.coluna += linha.chars().count();
+        linha
+    }
+    
+    fn ler_comentario_doc(&mut self) -> Token<'a> {
+        Token::ComentarioDoc(texto_documentacao(self.ler_ate_fim_da_linha()))
+    }
+    
+    fn restaurar(&mut self, marca: Marca) {
+        self.byte = marca.byte;
+        self.linha = marca.linha;
+        self.coluna = marca.coluna;
+    }
+    
+    /// Tenta ler, em sequência, as palavras informadas (separadas por espaços
+    /// ou tabulações, sem quebras de linha, como na gramática), para reconhecer palavras-chave compostas como `para cada`. Se alguma
+    /// palavra não corresponder, a posição é restaurada e nada é consumido.
+    fn tentar_palavras(&mut self, palavras: &[&str]) -> bool {
+        let marca = self.marca();
+        
+        for palavra in palavras {
+            self.avancar_enquanto(|c| c == ' ' || c == '\t');
+            
+            let inicia_identificador = self.caractere_atual()
+                .is_some_and(eh_inicio_identificador);
+            if !inicia_identificador || self.ler_identificador() != *palavra {
+                self.restaurar(marca);
+                return false;
+            }
+        }
+        
+        true
+    }
+    
+    /// Avança enquanto o caractere atual satisfizer o predicado. Nenhum dos
+    /// predicados usados aceita quebras de linha, então só a coluna muda.
+    fn avancar_enquanto(&mut self, predicado: impl Fn(char) -> bool) {
+        let restante = self.restante();
+        let mut bytes = 0;
+        for c in restante.chars() {
+            if c == '\n' || !predicado(c) {
                 break;
             }
+            bytes += c.len_utf8();
+            self.coluna += 1;
         }
+        self.byte += bytes;
+    }
+    
+    fn ler_identificador(&mut self) -> Cow<'a, str> {
+        let inicio = self.byte;
+        self.avancar_enquanto(eh_continuacao_identificador);
+        normalizar_identificador(&self.entrada[inicio..self.byte])
+    }
+    
+    /// Avança sobre dígitos da base informada, aceitando `_` como separador
+    fn pular_digitos(&mut self, base: u32) {
+        self.avancar_enquanto(|c| c.is_digit(base) || c == '_');
+    }
+    
+    /// Lê um literal numérico: inteiros (`42`, `1_000_000`, `0xFF`, `0b1010`,
+    /// `0o17`) ou decimais (`3.14`, `1.5e-3`, `2E10`)
+    fn ler_numero(&mut self) -> Result<Token<'a>, LexerError> {
+        let marca = self.marca();
+        
+        let base = match (self.caractere_atual(), self.proximo_caractere()) {
+            (Some('0'), Some('x' | 'X')) => 16,
+            (Some('0'), Some('b' | 'B')) => 2,
+            (Some('0'), Some('o' | 'O')) => 8,
+            _ => 10,
+        };
         
-        num.parse::<f64>().map_err(|_| LexerError::ErroNumero(num))
+        if base != 10 {
+            // Pular o prefixo
+            self.avancar_ascii(2);
+        }
+        
+        let inicio_digitos = self.byte;
+        let mut decimal = false;
+        self.pular_digitos(base);
+        
+        if base == 10 {
+            // Parte fracionária: o ponto só pertence ao número se for seguido
+            // de um dígito
+            if self.caractere_atual() == Some('.')
+                && self.proximo_caractere().is_some_and(|c| c.is_ascii_digit())
+            {
+                decimal = true;
+                self.avancar_ascii(1);
+                self.pular_digitos(10);
+            }
+            
+            // Expoente
+            if let Some('e' | 'E') = self.caractere_atual() {
+                let mut seguintes = self.restante()[1..].chars();
+                let tem_expoente = match seguintes.next() {
+                    Some(c) if c.is_ascii_digit() => true,
+                    Some('+' | '-') => seguintes.next().is_some_and(|c| c.is_ascii_digit()),
+                    _ => false,
+                };
+                
+                if tem_expoente {
+                    decimal = true;
+                    self.avancar_ascii(1);
+                    if let Some('+' | '-') = self.caractere_atual() {
+                        self.avancar_ascii(1);
+                    }
+                    self.pular_digitos(10);
+                }
+            }
+        }
+        
+        // Um número não pode ser seguido diretamente de letras (ex: `12abc`, `0xFG`)
+        self.avancar_enquanto(eh_continuacao_identificador);
+        
+        let erro = |lexer: &Self| LexerError::ErroNumero {
+            texto: lexer.entrada[marca.byte..lexer.byte].to_string(),
+            inicio: marca.byte,
+            fim: lexer.byte,
+            linha: marca.linha,
+            coluna: marca.coluna,
+        };
+        
+        // O separador `_` só é permitido entre dígitos
+        let digitos = &self.entrada[inicio_digitos..self.byte];
+        let digitos_validos = !digitos.is_empty()
+            && !digitos.starts_with('_')
+            && digitos.char_indices().all(|(i, c)| {
+                c != '_' || digitos[i + 1..].chars().next().is_some_and(|d| d == '_' || d.is_digit(base))
+            });
+        if !digitos_validos {
+            return Err(erro(self));
+        }
+        
+        let limpo = if digitos.contains('_') {
+            Cow::Owned(digitos.replace('_', ""))
+        } else {
+            Cow::Borrowed(digitos)
+        };
+        
+        if decimal {
+            match limpo.parse::<f64>() {
+                Ok(valor) if valor.is_finite() => Ok(Token::NumeroLiteral(valor)),
+                _ => Err(erro(self)),
+            }
+        } else {
+            i64::from_str_radix(&limpo, base)
+                .map(Token::InteiroLiteral)
+                .map_err(|_| erro(self))
+        }
     }
     
-    fn ler_texto(&mut self) -> Result<String, LexerError> {
-        let mut texto = String::new();
-        self.avancar(); // Pular a aspas inicial
+    /// Lê um trecho de texto até a aspas final ou até o início de uma
+    /// interpolação (`${`). Deve ser chamado logo após a aspas inicial ou
+    /// após a `}` que fecha uma interpolação.
+    fn ler_parte_texto(&mut self, marca: Marca, inicio_texto: bool) -> Result<Token<'a>, LexerError> {
+        let marca_texto = if inicio_texto {
+            marca
+        } else {
+            // A interpolação terminou; os erros continuam apontando para a
+            // aspas inicial do texto
+            self.interpolacoes.pop().map_or(marca, |i| i.texto)
+        };
+        let inicio = self.byte;
+        // Só é alocado quando aparece uma sequência de escape
+        let mut decodificado: Option<String> = None;
         
         while let Some(c) = self.caractere_atual() {
-            if c == '"' {
-                self.avancar(); // Pular a aspas final
-                return Ok(texto);
-            } else {
-                texto.push(c);
-                self.avancar();
+            match c {
+                '"' | '$' if c == '"' || self.proximo_caractere() == Some('{') => {
+                    let texto = match decodificado.take() {
+                        Some(texto) => Cow::Owned(texto),
+                        None => Cow::Borrowed(&self.entrada[inicio..self.byte]),
+                    };
+                    
+                    if c == '"' {
+                        self.avancar_ascii(1); // Pular a aspas final
+                        return Ok(if inicio_texto {
+                            Token::TextoLiteral(texto)
+                        } else {
+                            Token::TextoFim(texto)
+                        });
+                    }
+                    
+                    self.avancar_ascii(2); // Pular o `${`
+                    self.interpolacoes.push(Interpolacao {
+                        texto: marca_texto,
+                        profundidade: 0,
+                    });
+                    return Ok(if inicio_texto {
+                        Token::TextoInicio(texto)
+                    } else {
+                        Token::TextoMeio(texto)
+                    });
+                },
+                '\\' => {
+                    let texto = decodificado
+                        .get_or_insert_with(|| self.entrada[inicio..self.byte].to_string());
+                    match self.ler_escape() {
+                        Ok(c) => texto.push(c),
+                        // O texto continua válido; o escape vira U+FFFD
+                        Err(erro) if self.recuperando => {
+                            texto.push(char::REPLACEMENT_CHARACTER);
+                            self.erros.push(erro);
+                        },
+                        Err(erro) => return Err(erro),
+                    }
+                },
+                _ => {
+                    if let Some(texto) = decodificado.as_mut() {
+                        texto.push(c);
+                    }
+                    self.avancar();
+                }
+            }
+        }
+        
+        Err(LexerError::StringNaoTerminada {
+            inicio: marca_texto.byte,
+            fim: self.byte,
+            linha: marca_texto.linha,
+            coluna: marca_texto.coluna,
+        })
+    }
+    
+    /// Lê um texto de várias linhas (`"""..."""`), que é usado como está,
+    /// sem escapes nem interpolações
+    fn ler_texto_multilinha(&mut self, marca: Marca) -> Result<Token<'a>, LexerError> {
+        self.avancar_ascii(3); // Pular as aspas iniciais
+        let inicio = self.byte;
+        while self.caractere_atual().is_some() {
+            if self.comeca_com("\"\"\"") {
+                let texto = &self.entrada[inicio..self.byte];
+                self.avancar_ascii(3); // Pular as aspas finais
+                return Ok(Token::TextoLiteral(Cow::Borrowed(texto)));
             }
+            self.avancar();
+        }
+        
+        Err(LexerError::StringNaoTerminada {
+            inicio: marca.byte,
+            fim: self.byte,
+            linha: marca.linha,
+            coluna: marca.coluna,
+        })
+    }
+    
+    /// Decodifica uma sequência de escape iniciada em `\`
+    fn ler_escape(&mut self) -> Result<char, LexerError> {
+        let marca = self.marca();
+        self.avancar(); // Pular a barra invertida
+        
+        let c = match self.caractere_atual() {
+            Some(c) => c,
+            None => return Err(self.escape_invalido(marca)),
+        };
+        self.avancar();
+        
+        match c {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '\'' => Ok('\''),
+            '$' => Ok('$'),
+            'u' => {
+                // Formato \u{XXXX}, com 1 a 6 dígitos hexadecimais
+                if self.caractere_atual() != Some('{') {
+                    return Err(self.escape_invalido(marca));
+                }
+                self.avancar();
+                
+                let inicio = self.byte;
+                self.avancar_enquanto(|c| c.is_ascii_hexdigit());
+                let hex = &self.entrada[inicio..self.byte];
+                
+                if self.caractere_atual() != Some('}') || hex.is_empty() || hex.len() > 6 {
+                    return Err(self.escape_invalido(marca));
+                }
+                self.avancar();
+                
+                u32::from_str_radix(hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.escape_invalido(marca))
+            },
+            _ => Err(self.escape_invalido(marca)),
+        }
+    }
+    
+    fn escape_invalido(&self, marca: Marca) -> LexerError {
+        let sequencia = self.entrada[marca.byte..self.byte].to_string();
+        LexerError::EscapeInvalido {
+            sequencia,
+            inicio: marca.byte,
+            fim: self.byte,
+            linha: marca.linha,
+            coluna: marca.coluna,
+        }
+    }
+    
+    /// Lê o próximo token junto com a sua posição no código fonte
+    pub fn proximo_token(&mut self) -> Result<TokenComPosicao<'a>, LexerError> {
+        self.pular_espacos_e_comentarios()?;
+        self.ler_token_com_posicao()
+    }
+    
+    /// Como `proximo_token`, mas devolve também a trivia que precede o token
+    pub fn proximo_token_com_trivia(&mut self) -> Result<(Vec<Trivia<'a>>, TokenComPosicao<'a>), LexerError> {
+        let mut trivia = Vec::new();
+        while let Some(trecho) = self.ler_trivia()? {
+            trivia.push(trecho);
         }
         
-        Err(LexerError::StringNaoTerminada)
+        Ok((trivia, self.ler_token_com_posicao()?))
     }
     
-    pub fn proximo_token(&mut self) -> Result<Token, LexerError> {
-        self.pular_espacos();
+    fn ler_token_com_posicao(&mut self) -> Result<TokenComPosicao<'a>, LexerError> {
+        let marca = self.marca();
+        let token = self.ler_token(marca)?;
+        
+        Ok(TokenComPosicao {
+            token,
+            inicio: marca.byte,
+            fim: self.byte,
+            linha: marca.linha,
+            coluna: marca.coluna,
+        })
+    }
+    
+    /// Como `proximo_token`, mas nunca falha: o erro é guardado em
+    /// `self.erros`, o lexer avança para depois do trecho inválido e um
+    /// `Token::Erro` ocupa o seu lugar
+    fn proximo_token_recuperando(&mut self) -> TokenComPosicao<'a> {
+        let erro = match self.proximo_token() {
+            Ok(token) => return token,
+            Err(erro) => erro,
+        };
+        
+        let intervalo = erro.intervalo();
+        let (linha, coluna) = erro.linha_coluna();
+        
+        match erro {
+            // Caracteres inválidos nem sempre são consumidos por `ler_token`
+            LexerError::CaractereInesperado { .. } => {
+                while self.byte < intervalo.end {
+                    self.avancar();
+                }
+            },
+            // O texto foi lido até o fim do arquivo; não há mais interpolação
+            // a fechar
+            LexerError::StringNaoTerminada { .. } => self.interpolacoes.clear(),
+            // Números inválidos e comentários não terminados já foram
+            // consumidos por inteiro
+            _ => {},
+        }
         
+        self.erros.push(erro);
+        TokenComPosicao {
+            token: Token::Erro,
+            inicio: intervalo.start,
+            fim: self.byte.max(intervalo.end),
+            linha,
+            coluna,
+        }
+    }
+    
+    fn ler_token(&mut self, marca: Marca) -> Result<Token<'a>, LexerError> {
         match self.caractere_atual() {
-            None => Ok(Token::EOF),
+            None => match self.interpolacoes.last() {
+                // O arquivo terminou dentro de um `${ ... }`
+                Some(interpolacao) => Err(LexerError::StringNaoTerminada {
+                    inicio: interpolacao.texto.byte,
+                    fim: self.byte,
+                    linha: interpolacao.texto.linha,
+                    coluna: interpolacao.texto.coluna,
+                }),
+                None => Ok(Token::EOF),
+            },
             
             Some(c) => {
                 match c {
                     // Operadores e símbolos
-                    '{' => { self.avancar(); Ok(Token::AbreChave) },
-                    '}' => { self.avancar(); Ok(Token::FechaChave) },
-                    '(' => { self.avancar(); Ok(Token::AbreParentese) },
-                    ')' => { self.avancar(); Ok(Token::FechaParentese) },
-                    ',' => { self.avancar(); Ok(Token::Virgula) },
-                    ':' => { self.avancar(); Ok(Token::DoisPontos) },
-                    ';' => { self.avancar(); Ok(Token::PontoVirgula) },
-                    '+' => { self.avancar(); Ok(Token::Mais) },
-                    '-' => { self.avancar(); Ok(Token::Menos) },
-                    '*' => { self.avancar(); Ok(Token::Vezes) },
-                    '/' => { 
-                        self.avancar();
-                        // Verificar se é comentário
-                        if let Some('/') = self.caractere_atual() {
-                            // Pular até o final da linha
-                            while let Some(c) = self.caractere_atual() {
-                                if c == '\n' {
-                                    self.avancar();
-                                    break;
-                                }
-                                self.avancar();
-                            }
-                            self.proximo_token() // Retornar o próximo token após o comentário
-                        } else {
-                            Ok(Token::Dividido)
-                        }
-                    },
-                    '=' => { self.avancar(); Ok(Token::Igual) },
-                    '>' => {
-                        self.avancar();
-                        if let Some('=') = self.caractere_atual() {
-                            self.avancar();
-                            Ok(Token::MaiorIgual)
-                        } else {
-                            Ok(Token::Maior)
-                        }
-                    },
-                    '<' => {
+                    '{' => {
                         self.avancar();
-                        if let Some('=') = self.caractere_atual() {
-                            self.avancar();
-                            Ok(Token::MenorIgual)
-                        } else {
-                            Ok(Token::Menor)
+                        if let Some(interpolacao) = self.interpolacoes.last_mut() {
+                            interpolacao.profundidade += 1;
                         }
+                        Ok(Token::AbreChave)
                     },
-                    '!' => {
+                    '}' => {
                         self.avancar();
-                        if let Some('=') = self.caractere_atual() {
-                            self.avancar();
-                            Ok(Token::Diferente)
-                        } else {
-                            Err(LexerError::CaractereInesperado('!'))
+                        match self.interpolacoes.last_mut() {
+                            // Fim de uma interpolação: continuar lendo o texto
+                            Some(interpolacao) if interpolacao.profundidade == 0 => {
+                                self.ler_parte_texto(marca, false)
+                            },
+                            Some(interpolacao) => {
+                                interpolacao.profundidade -= 1;
+                                Ok(Token::FechaChave)
+                            },
+                            None => Ok(Token::FechaChave),
                         }
                     },
+                    '(' => { self.avancar(); Ok(Token::AbreParentese) },
+                    ')' => { self.avancar(); Ok(Token::FechaParentese) },
+                    '[' => { self.avancar(); Ok(Token::AbreColchete) },
+                    ']' => { self.avancar(); Ok(Token::FechaColchete) },
+                    ',' => { self.avancar(); Ok(Token::Virgula) },
+                    '.' if self.comeca_com("..") => { self.avancar_ascii(2); Ok(Token::Intervalo) },
+                    '.' => { self.avancar(); Ok(Token::Ponto) },
+                    ':' => { self.avancar(); Ok(Token::DoisPontos) },
+                    ';' => { self.avancar(); Ok(Token::PontoVirgula) },
+                    '+' => Ok(self.operador_com_igual(Token::Mais, Token::MaisIgual)),
+                    '-' if self.comeca_com("->") => { self.avancar_ascii(2); Ok(Token::Seta) },
+                    '-' => Ok(self.operador_com_igual(Token::Menos, Token::MenosIgual)),
+                    '*' => Ok(self.operador_com_igual(Token::Vezes, Token::VezesIgual)),
+                    '/' if self.comeca_comentario_doc() => Ok(self.ler_comentario_doc()),
+                    '/' => Ok(self.operador_com_igual(Token::Dividido, Token::DivididoIgual)),
+                    '%' => Ok(self.operador_com_igual(Token::Resto, Token::RestoIgual)),
+                    '=' => Ok(self.operador_com_igual(Token::Igual, Token::IgualIgual)),
+                    '>' => Ok(self.operador_com_igual(Token::Maior, Token::MaiorIgual)),
+                    '<' => Ok(self.operador_com_igual(Token::Menor, Token::MenorIgual)),
+                    '!' => Ok(self.operador_com_igual(Token::Nao, Token::Diferente)),
                     
-                    // Literais de texto
-                    '"' => self.ler_texto().map(Token::TextoLiteral),
+                    // Literais de texto; `"""` vem primeiro, como na gramática
+                    '"' if self.comeca_com("\"\"\"") => self.ler_texto_multilinha(marca),
+                    '"' => {
+                        self.avancar(); // Pular a aspas inicial
+                        self.ler_parte_texto(marca, true)
+                    },
                     
                     // Números
-                    '0'..='9' => self.ler_numero().map(Token::NumeroLiteral),
+                    '0'..='9' => self.ler_numero(),
                     
                     // Identificadores e palavras-chave
-                    'a'..='z' | 'A'..='Z' | '_' | 'á'..='ú' | 'Á'..='Ú' => {
+                    c if eh_inicio_identificador(c) => {
                         let id = self.ler_identificador();
                         
-                        match id.as_str() {
+                        match id.as_ref() {
                             "faça" => Ok(Token::Faca),
                             "mostre" => Ok(Token::Mostre),
                             "volte" => Ok(Token::Volte),
                             "se" => Ok(Token::Se),
                             "senão" => Ok(Token::Senao),
-                            "para" => {
-                                // Verifica se é "para cada"
-                                self.pular_espacos();
-                                if let Some(c) = self.caractere_atual() {
-                                    if c == 'c' {
-                                        let resto = self.ler_identificador();
-                                        if resto == "cada" {
-                                            return Ok(Token::ParaCada);
-                                        }
-                                    }
-                                }
-                                Ok(Token::Identificador(format!("para_{}", self.ler_identificador())))
-                            },
-                            "cada" => Ok(Token::Identificador("cada".to_string())),
+                            "para" if self.tentar_palavras(&["cada"]) => Ok(Token::ParaCada),
                             "de" => Ok(Token::De),
                             "até" | "ate" => Ok(Token::Ate),
                             "modelo" => Ok(Token::Modelo),
-                            "quando" => {
-                                // Verifica se é "quando der erro"
-                                self.pular_espacos();
-                                if let Some(c) = self.caractere_atual() {
-                                    if c == 'd' {
-                                        let der = self.ler_identificador();
-                                        if der == "der" {
-                                            self.pular_espacos();
-                                            if let Some(c) = self.caractere_atual() {
-                                                if c == 'e' {
-                                                    let erro = self.ler_identificador();
-                                                    if erro == "erro" {
-                                                        return Ok(Token::QuandoDerErro);
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                                Ok(Token::Identificador("quando".to_string()))
-                            },
-                            "falhar" => {
-                                // Verifica se é "falhar com"
-                                self.pular_espacos();
-                                if let Some(c) = self.caractere_atual() {
-                                    if c == 'c' {
-                                        let com = self.ler_identificador();
-                                        if com == "com" {
-                                            return Ok(Token::FalharCom);
-                                        }
-                                    }
-                                }
-                                Ok(Token::Identificador("falhar".to_string()))
-                            },
+                            "quando" if self.tentar_palavras(&["der", "erro"]) => Ok(Token::QuandoDerErro),
+                            "falhar" if self.tentar_palavras(&["com"]) => Ok(Token::FalharCom),
                             "pense" => Ok(Token::Pense),
                             "verdadeiro" => Ok(Token::Verdadeiro),
                             "falso" => Ok(Token::Falso),
@@ -316,27 +926,15 @@ impl Lexer {
                             "texto" => Ok(Token::TipoTexto),
                             "número" | "numero" => Ok(Token::TipoNumero),
                             "lógico" | "logico" => Ok(Token::TipoLogico),
+                            "inteiro" => Ok(Token::TipoInteiro),
                             
-                            // Comparações especiais
-                            "é" | "e" => {
-                                // Verifica se é "é igual a"
-                                self.pular_espacos();
-                                if let Some(c) = self.caractere_atual() {
-                                    if c == 'i' {
-                                        let igual = self.ler_identificador();
-                                        if igual == "igual" {
-                                            self.pular_espacos();
-                                            if let Some(c) = self.caractere_atual() {
-                                                if c == 'a' {
-                                                    self.avancar();
-                                                    return Ok(Token::IgualA);
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                                Ok(Token::Identificador("é".to_string()))
-                            },
+                            // Operadores escritos por extenso
+                            "é" if self.tentar_palavras(&["igual", "a"]) => Ok(Token::IgualA),
+                            "e" => Ok(Token::E),
+                            "ou" => Ok(Token::Ou),
+                            "não" => Ok(Token::Nao),
+                            "em" => Ok(Token::Em),
+                            "resto" => Ok(Token::Resto),
                             
                             // Identificador normal
                             _ => Ok(Token::Identificador(id)),
@@ -344,28 +942,134 @@ impl Lexer {
                     },
                     
                     // Qualquer outro caractere é um erro
-                    _ => Err(LexerError::CaractereInesperado(c)),
+                    _ => Err(self.caractere_inesperado(c, marca)),
                 }
             }
         }
     }
+    
+    /// Lê um operador de um caractere ASCII que tem uma variante seguida de
+    /// `=` (como `+` e `+=`)
+    fn operador_com_igual(&mut self, simples: Token<'a>, com_igual: Token<'a>) -> Token<'a> {
+        self.avancar_ascii(1);
+        if self.caractere_atual() == Some('=') {
+            self.avancar_ascii(1);
+            com_igual
+        } else {
+            simples
+        }
+    }
+    
+    fn caractere_inesperado(&self, caractere: char, marca: Marca) -> LexerError {
+        LexerError::CaractereInesperado {
+            caractere,
+            inicio: marca.byte,
+            fim: marca.byte + caractere.len_utf8(),
+            linha: marca.linha,
+            coluna: marca.coluna,
+        }
+    }
+}
+
+/// Um identificador começa com um caractere `XID_Start` (letras de qualquer
+/// alfabeto, incluindo as acentuadas) ou `_`. A gramática pest segue a mesma
+/// regra.
+pub fn eh_inicio_identificador(c: char) -> bool {
+    c.is_xid_start() || c == '_'
+}
+
+/// Caracteres `XID_Continue`: letras, dígitos, `_` e marcas combinantes
+pub fn eh_continuacao_identificador(c: char) -> bool {
+    c.is_xid_continue()
+}
+
+/// Normaliza um identificador na forma NFC, para que `número` digitado com
+/// acento combinante (`u` + U+0301) seja igual à forma pré-composta. Só aloca
+/// quando o nome ainda não está normalizado.
+pub fn normalizar_identificador(nome: &str) -> Cow<'_, str> {
+    if is_nfc_quick(nome.chars()) == IsNormalized::Yes {
+        Cow::Borrowed(nome)
+    } else {
+        Cow::Owned(nome.nfc().collect())
+    }
+}
+
+/// Extrai o texto de uma linha de comentário de documentação, removendo
+/// o `///` inicial, um espaço opcional e os espaços do final
+pub fn texto_documentacao(linha: &str) -> &str {
+    let linha = linha.trim();
+    let texto = linha.strip_prefix("///").unwrap_or(linha);
+    texto.strip_prefix(' ').unwrap_or(texto).trim_end()
+}
+
+/// Decodifica as sequências de escape (`\n`, `\"`, `\u{...}`, ...) de um
+/// texto que já teve as aspas removidas.
+pub fn decodificar_escapes(conteudo: &str) -> Result<String, LexerError> {
+    let mut lexer = Lexer::new(conteudo);
+    let mut texto = String::new();
+    
+    while let Some(c) = lexer.caractere_atual() {
+        if c == '\\' {
+            texto.push(lexer.ler_escape()?);
+        } else {
+            texto.push(c);
+            lexer.avancar();
+        }
+    }
+    
+    Ok(texto)
+}
+
+/// Interpreta o texto de um literal numérico, produzindo
+/// `Token::InteiroLiteral` ou `Token::NumeroLiteral`
+pub fn literal_numerico(texto: &str) -> Result<Token<'_>, LexerError> {
+    let mut lexer = Lexer::new(texto);
+    let token = lexer.ler_numero()?;
+    
+    match lexer.caractere_atual() {
+        None => Ok(token),
+        Some(c) => Err(lexer.caractere_inesperado(c, lexer.marca())),
+    }
 }
 
-pub fn tokenizar(codigo: &str) -> Result<Vec<Token>> {
+/// Converte o código fonte em uma lista de tokens com suas posições.
+/// O último token é sempre `Token::EOF`.
+pub fn tokenizar(codigo: &str) -> Result<Vec<TokenComPosicao<'_>>> {
     let mut lexer = Lexer::new(codigo);
     let mut tokens = Vec::new();
     
     loop {
-        match lexer.proximo_token() {
-            Ok(Token::EOF) => {
-                tokens.push(Token::EOF);
-                break;
-            },
-            Ok(token) => tokens.push(token),
-            Err(e) => return Err(anyhow::Error::new(e)),
+        let token = lexer.proximo_token().map_err(anyhow::Error::new)?;
+        let fim = token.token == Token::EOF;
+        tokens.push(token);
+        
+        if fim {
+            break;
         }
     }
     
     Ok(tokens)
 }
 
+/// Tokeniza o código inteiro mesmo na presença de erros léxicos, para que
+/// todos os problemas possam ser relatados de uma só vez. Cada trecho
+/// inválido vira um `Token::Erro`; os erros são devolvidos na ordem em que
+/// aparecem no código.
+pub fn tokenizar_com_recuperacao(codigo: &str) -> (Vec<TokenComPosicao<'_>>, Vec<LexerError>) {
+    let mut lexer = Lexer::new(codigo);
+    lexer.recuperando = true;
+    let mut tokens = Vec::new();
+    
+    loop {
+        let token = lexer.proximo_token_recuperando();
+        let fim = token.token == Token::EOF;
+        tokens.push(token);
+        
+        if fim {
+            break;
+        }
+    }
+    
+    (tokens, lexer.erros)
+}
+
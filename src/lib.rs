@@ -3,10 +3,11 @@ pub mod lexer;
 pub mod parser;
 pub mod transpiler;
 pub mod caixotes;
+pub mod cst;
 
 // Re-export commonly used items
-pub use ast::{Declaracao, Expressao, Operador, Programa, Tipo};
-pub use parser::{analisar_codigo, PBRParser};
-pub use lexer::tokenizar;
+pub use ast::{Declaracao, Expressao, Operador, ParteTexto, Programa, Tipo};
+pub use parser::{analisar_codigo, analisar_com_recuperacao, ErroSintatico, PBRParser};
+pub use lexer::{tokenizar, tokenizar_com_recuperacao, TokenComPosicao};
 pub use transpiler::gerar_codigo_rust;
 
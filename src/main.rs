@@ -1,14 +1,10 @@
-mod ast;
-mod caixotes;
-mod lexer;
-mod parser;
-mod transpiler;
+use pbrlang::{ast, lexer, parser, transpiler};
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use colored::Colorize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "pbr")]
@@ -108,6 +104,9 @@ enum ComandosCaixote {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    // Guarda as tentativas do pest, que os erros de sintaxe usam para
+    // descrever o que era esperado
+    pest::set_error_detail(true);
 
     match cli.comando {
         Comandos::Novo { nome } => criar_novo_projeto(nome),
@@ -184,11 +183,11 @@ fn executar_testes(caminho: PathBuf) -> Result<()> {
             let entry = entry?;
             let path = entry.path();
             
-            if path.is_file() && path.extension().map_or(false, |ext| ext == "pbr") {
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "pbr") {
                 arquivos_teste.push(path);
             }
         }
-    } else if caminho.extension().map_or(false, |ext| ext == "pbr") {
+    } else if caminho.extension().is_some_and(|ext| ext == "pbr") {
         // Se for arquivo .pbr, adicionar diretamente
         arquivos_teste.push(caminho);
     } else {
@@ -236,6 +235,46 @@ fn executar_testes(caminho: PathBuf) -> Result<()> {
     }
 }
 
+/// Analisa o código com o parser da gramática, o único usado pela CLI. Se
+/// a análise falha, o código é tokenizado para relatar todos os erros léxicos
+/// de uma vez, em vez de parar no primeiro; o lexer só é consultado então,
+/// para que nunca rejeite um programa que a gramática aceita.
+fn analisar_programa(codigo: &str, arquivo: &Path) -> Result<ast::Programa> {
+    parser::analisar_codigo(codigo).map_err(|erro| {
+        if let Err(erro_lexico) = verificar_lexico(codigo, arquivo) {
+            return erro_lexico;
+        }
+        if !erro.is::<parser::ErroSintatico>() {
+            return erro.context("Erro na análise sintática");
+        }
+        
+        // Analisa de novo, continuando depois de cada erro, para mostrar
+        // todos os erros de sintaxe de uma vez
+        let (_, mut erros) = parser::analisar_com_recuperacao(codigo);
+        if erros.is_empty() {
+            erros.extend(erro.downcast::<parser::ErroSintatico>());
+        }
+        for erro in &erros {
+            eprintln!("{} {}, {}", "erro:".red().bold(), arquivo.display(), erro);
+        }
+        anyhow::anyhow!("Erro na análise sintática: {} problema(s) encontrado(s)", erros.len())
+    })
+}
+
+fn verificar_lexico(codigo: &str, arquivo: &Path) -> Result<()> {
+    let (_, erros) = lexer::tokenizar_com_recuperacao(codigo);
+    
+    if erros.is_empty() {
+        return Ok(());
+    }
+    
+    for erro in &erros {
+        eprintln!("{} {}: {}", "erro:".red().bold(), arquivo.display(), erro);
+    }
+    
+    Err(anyhow::anyhow!("Erro na análise léxica: {} problema(s) encontrado(s)", erros.len()))
+}
+
 fn executar_programa(arquivo: PathBuf) -> Result<()> {
     println!("{} {}", "Executando:".green().bold(), arquivo.display());
     
@@ -244,22 +283,18 @@ fn executar_programa(arquivo: PathBuf) -> Result<()> {
         .with_context(|| format!("Não foi possível ler o arquivo: {}", arquivo.display()))?;
     
     // Analisar e executar o código
-    let tokens = lexer::tokenizar(&codigo)
-        .with_context(|| "Erro na análise léxica")?;
-    
-    let ast = parser::analisar(tokens)
-        .with_context(|| "Erro na análise sintática")?;
+    let ast = analisar_programa(&codigo, &arquivo)?;
     
     let codigo_rust = transpiler::gerar_codigo_rust(ast)
         .with_context(|| "Erro na geração de código Rust")?;
     
     // Gerar um arquivo temporário com o código Rust
-    let mut temp_dir = tempfile::tempdir()
+    let temp_dir = tempfile::tempdir()
         .with_context(|| "Falha ao criar diretório temporário")?;
     
     let rust_file_path = temp_dir.path().join("programa_gerado.rs");
     fs::write(&rust_file_path, &codigo_rust)
-        .with_context(|| format!("Não foi possível escrever o arquivo temporário"))?;
+        .with_context(|| "Não foi possível escrever o arquivo temporário")?;
 
     // Exibir o código gerado (opcional, pode ser controlado por flag)
     println!("Código Rust gerado:");
@@ -269,8 +304,6 @@ fn executar_programa(arquivo: PathBuf) -> Result<()> {
     println!("\n{}", "Compilando o código...".yellow().bold());
     
     let output_path = temp_dir.path().join("programa_executavel");
-    let output_path_str = output_path.to_string_lossy();
-    
     let compile_status = std::process::Command::new("rustc")
         .arg(&rust_file_path)
         .arg("-o")
@@ -307,11 +340,7 @@ fn converter_para_rust(arquivo: PathBuf, saida: Option<PathBuf>, apenas_gerar: b
         .with_context(|| format!("Não foi possível ler o arquivo: {}", arquivo.display()))?;
     
     // Analisar o código
-    let tokens = lexer::tokenizar(&codigo)
-        .with_context(|| "Erro na análise léxica")?;
-    
-    let ast = parser::analisar(tokens)
-        .with_context(|| "Erro na análise sintática")?;
+    let ast = analisar_programa(&codigo, &arquivo)?;
     
     let codigo_rust = transpiler::gerar_codigo_rust(ast)
         .with_context(|| "Erro na geração de código Rust")?;
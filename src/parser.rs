@@ -1,32 +1,372 @@
-use crate::ast::{Declaracao, Expressao, Operador, Programa, Tipo};
-use crate::lexer::Token;
-use anyhow::{anyhow, Context, Result};
+use crate::ast::{Assinatura, Declaracao, Expressao, Operador, Padrao, ParteTexto, Programa, Tipo};
+use crate::lexer::{
+    decodificar_escapes, literal_numerico, normalizar_identificador, texto_documentacao,
+    tokenizar_com_recuperacao, Lexer, LexerError, Token, TokenComPosicao,
+};
+use anyhow::{anyhow, Result};
 use pest::Parser;
-use pest::iterators::{Pair, Pairs};
+use pest::error::InputLocation;
+use pest::iterators::Pair;
+use pest::pratt_parser::{Assoc, Op, PrattParser};
+use pest::Position;
 use pest_derive::Parser;
+use std::fmt;
+use std::ops::Range;
+use std::sync::LazyLock;
 
 #[derive(Parser)]
 #[grammar = "grammar.pest"]
 pub struct PBRParser;
 
-/// Converte uma lista de tokens em uma AST
-pub fn analisar(tokens: Vec<Token>) -> Result<Programa> {
-    // Por enquanto, esta é apenas uma implementação básica que constrói
-    // uma AST simples diretamente a partir dos tokens
+/// Precedência dos operadores, da menor para a maior. Todos os binários
+/// associam à esquerda; os prefixos (`-`, `não`) ficam abaixo só dos
+/// sufixos (`.`, `()`, `[]`), que fazem parte do fator.
+static PRECEDENCIA: LazyLock<PrattParser<Rule>> = LazyLock::new(|| {
+    PrattParser::new()
+        .op(Op::infix(Rule::op_ou, Assoc::Left))
+        .op(Op::infix(Rule::op_e, Assoc::Left))
+        .op(Op::infix(Rule::op_comparacao, Assoc::Left))
+        .op(Op::infix(Rule::op_soma, Assoc::Left))
+        .op(Op::infix(Rule::op_termo, Assoc::Left))
+        .op(Op::prefix(Rule::op_negativo) | Op::prefix(Rule::op_negacao))
+});
+
+/// Erro de sintaxe, com a posição e o trecho do código onde aconteceu.
+///
+/// Ao ser exibido, mostra a linha do código com um `^` embaixo do trecho
+/// encontrado:
+///
+/// ```text
+/// linha 1, coluna 10: esperava `{`, encontrou `mostre`
+///   |
+/// 1 | se x > 1 mostre x;
+///   |          ^^^^^^
+/// ```
+///
+/// Os símbolos esperados (`{`, `;`) vêm das tentativas do pest, que só são
+/// guardadas com `pest::set_error_detail(true)`. Como isso vale para todo o
+/// processo, quem usa a biblioteca decide se ativa; a CLI ativa ao iniciar.
+/// Sem as tentativas, o erro menciona só as regras esperadas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErroSintatico {
+    pub linha: usize,
+    pub coluna: usize,
+    // O que poderia aparecer na posição, já em forma legível ("`{`", "uma expressão")
+    pub esperado: Vec<String>,
+    // Trecho encontrado na posição; vazio no fim do arquivo
+    pub encontrado: String,
+    // Linha do código onde está o erro; vazio quando o código não está disponível
+    pub trecho: String,
+}
+
+impl ErroSintatico {
+    /// Converte um erro do pest, trocando os nomes das regras por descrições.
+    /// `deslocamento` é onde, em `codigo`, começa o trecho analisado pelo pest;
+    /// `numeros` são os trechos de `codigo` com números mal formados.
+    fn de_pest(codigo: &str, deslocamento: usize, erro: pest::error::Error<Rule>, numeros: &[Range<usize>]) -> Self {
+        let posicao_pest = match erro.location {
+            InputLocation::Pos(posicao) => posicao,
+            InputLocation::Span((inicio, _)) => inicio,
+        };
+        let tentativas = erro.parse_attempts();
+        let posicao = posicao_do_erro(&erro);
+        
+        // Um número mal formado (`12abc`, `1_`) é apontado por inteiro, como
+        // no lexer, em vez de no caractere em que a gramática parou
+        let absoluta = deslocamento + posicao;
+        if let Some(numero) = numeros.iter().find(|numero| numero.start <= absoluta && absoluta <= numero.end) {
+            let mut erro = Self::na_posicao(codigo, numero.start, vec!["um número válido".to_string()]);
+            erro.encontrado = codigo[numero.clone()].to_string();
+            return erro;
+        }
+        
+        let positivos = match &erro.variant {
+            pest::error::ErrorVariant::ParsingError { positives, .. } => positives.clone(),
+            _ => Vec::new(),
+        };
+        let regras = if posicao == posicao_pest { positivos.clone() } else { Vec::new() };
+        
+        // Prefere as regras estruturais (`{`, uma expressão) às que só
+        // continuariam a expressão anterior; quando só há continuações, o
+        // que falta é um símbolo da própria regra, como `;` ou `)`
+        let simbolos: Vec<String> = tentativas.iter()
+            .flat_map(|t| t.expected_tokens())
+            .map(|t| t.to_string())
+            .filter(|t| !simbolo_ignorado(t))
+            // `(` depois de um valor seria uma chamada, não o que faltou
+            .filter(|t| !(t == "(" && regras.contains(&Rule::argumentos)))
+            .collect();
+        // No fim de uma declaração simples, campo ou variante, a quebra de
+        // linha vem depois do separador (`;` ou `,`), e o `}` que também a
+        // encerraria fica subentendido
+        let fim_linha = regras.contains(&Rule::fim_linha);
+        let mut esperado = Vec::new();
+        for regra in regras.iter().filter(|r| !eh_continuacao(**r) && **r != Rule::fim_linha) {
+            adicionar_sem_repetir(&mut esperado, descrever_regra(*regra));
+        }
+        if fim_linha {
+            adicionar_sem_repetir(&mut esperado, descrever_regra(Rule::fim_linha));
+        }
+        let sem_regras = esperado.is_empty();
+        for simbolo in simbolos.iter().filter(|s| !(fim_linha && *s == "}")) {
+            // Com regras estruturais, só os fechamentos ainda interessam
+            if sem_regras || [";", ")", "]", "}"].contains(&simbolo.as_str()) {
+                adicionar_sem_repetir(&mut esperado, format!("`{}`", simbolo));
+            }
+        }
+        if esperado.is_empty() {
+            for regra in &regras {
+                adicionar_sem_repetir(&mut esperado, descrever_regra(*regra));
+            }
+        }
+        
+        // O ponto mais distante pode ter sido alcançado por uma alternativa
+        // que não leva a nada (`pense x =` seguido de `mostre 1` na linha de
+        // baixo); sem nada a esperar ali, vale a regra que falhou, na posição
+        // em que o pest parou
+        if esperado.is_empty() && !positivos.is_empty() {
+            for regra in &positivos {
+                adicionar_sem_repetir(&mut esperado, descrever_regra(*regra));
+            }
+            return Self::na_posicao(codigo, deslocamento + posicao_pest, esperado);
+        }
+        
+        Self::na_posicao(codigo, deslocamento + posicao, esperado)
+    }
+    
+    /// Cria um erro na posição `posicao` (em bytes) de `codigo`
+    fn na_posicao(codigo: &str, posicao: usize, esperado: Vec<String>) -> Self {
+        let local = Position::new(codigo, posicao).unwrap_or_else(|| Position::from_start(codigo));
+        let (linha, coluna) = local.line_col();
+        let trecho = local.line_of().trim_end_matches(['\n', '\r']).to_string();
+        
+        ErroSintatico {
+            linha,
+            coluna,
+            esperado,
+            encontrado: trecho_encontrado(&codigo[posicao..]).to_string(),
+            trecho,
+        }
+    }
+    
+    /// Cria um erro no início de `par`. A posição é relativa ao trecho que o
+    /// pest analisou (ver `deslocado`).
+    fn no_par(par: &Pair<Rule>, esperado: &str) -> Self {
+        let inicio = par.as_span().start_pos();
+        let (linha, coluna) = inicio.line_col();
+        
+        ErroSintatico {
+            linha,
+            coluna,
+            esperado: vec![esperado.to_string()],
+            encontrado: par.as_str().to_string(),
+            trecho: inicio.line_of().trim_end_matches(['\n', '\r']).to_string(),
+        }
+    }
+    
+    /// Leva para `codigo` um erro criado a partir do trecho de `codigo` que
+    /// começa em `deslocamento`
+    fn deslocado(self, codigo: &str, deslocamento: usize) -> Self {
+        let inicio = Position::new(codigo, deslocamento).unwrap_or_else(|| Position::from_start(codigo));
+        let (linha_inicio, coluna_inicio) = inicio.line_col();
+        let linha = linha_inicio + self.linha - 1;
+        let coluna = if self.linha == 1 { coluna_inicio + self.coluna - 1 } else { self.coluna };
+        let trecho = codigo.lines().nth(linha - 1).unwrap_or_default().trim_end_matches('\r').to_string();
+        
+        ErroSintatico { linha, coluna, trecho, ..self }
+    }
+    
+    /// Cria um erro do parser baseado em tokens, que não tem o código fonte
+    fn em_token(tokens: &[Token], posicoes: &[(usize, usize)], i: usize, esperado: &[&str]) -> anyhow::Error {
+        let (linha, coluna) = posicoes.get(i).or(posicoes.last()).copied().unwrap_or((1, 1));
+        let encontrado = tokens.get(i).map(|t| t.to_string()).unwrap_or_default();
+        
+        anyhow::Error::new(ErroSintatico {
+            linha,
+            coluna,
+            esperado: esperado.iter().map(|e| e.to_string()).collect(),
+            encontrado,
+            trecho: String::new(),
+        })
+    }
+}
+
+impl fmt::Display for ErroSintatico {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "linha {}, coluna {}: ", self.linha, self.coluna)?;
+        
+        match self.esperado.as_slice() {
+            [] => f.write_str("código inesperado")?,
+            [unico] => write!(f, "esperava {}", unico)?,
+            [anteriores @ .., ultimo] => write!(f, "esperava {} ou {}", anteriores.join(", "), ultimo)?,
+        }
+        
+        if self.encontrado.is_empty() {
+            f.write_str(", encontrou o fim do arquivo")?;
+        } else {
+            write!(f, ", encontrou `{}`", self.encontrado)?;
+        }
+        
+        if self.trecho.is_empty() {
+            return Ok(());
+        }
+        
+        // Trecho com o `^` alinhado à coluna, mantendo as tabulações da linha
+        let numero = self.linha.to_string();
+        let margem = " ".repeat(numero.len());
+        let recuo: String = self.trecho.chars()
+            .take(self.coluna - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let restante = self.trecho.chars().count().saturating_sub(self.coluna - 1);
+        let largura = self.encontrado.chars().count().clamp(1, restante.max(1));
+        
+        write!(f, "\n{} |\n{} | {}\n{} | {}{}", margem, numero, self.trecho, margem, recuo, "^".repeat(largura))
+    }
+}
+
+impl std::error::Error for ErroSintatico {}
+
+/// Posição do erro no trecho analisado: o ponto mais distante que o pest
+/// alcançou, que pode estar depois da regra que falhou
+fn posicao_do_erro(erro: &pest::error::Error<Rule>) -> usize {
+    let posicao_pest = match erro.location {
+        InputLocation::Pos(posicao) => posicao,
+        InputLocation::Span((inicio, _)) => inicio,
+    };
+    erro.parse_attempts()
+        .map_or(posicao_pest, |t| t.max_position.max(posicao_pest))
+}
+
+/// Trechos com números mal formados, segundo os erros do lexer
+fn numeros_invalidos(erros: &[LexerError]) -> Vec<Range<usize>> {
+    erros.iter()
+        .filter(|erro| matches!(erro, LexerError::ErroNumero { .. }))
+        .map(LexerError::intervalo)
+        .collect()
+}
+
+/// Regras que só continuariam a expressão anterior (`x + ...`); não são
+/// mencionadas quando outra coisa também era esperada
+fn eh_continuacao(regra: Rule) -> bool {
+    matches!(
+        regra,
+        Rule::op_atribuicao | Rule::op_ou | Rule::op_e | Rule::op_comparacao | Rule::op_soma
+            | Rule::op_termo | Rule::indice | Rule::acesso | Rule::argumentos
+    )
+}
+
+/// Descrição legível de uma regra da gramática
+fn descrever_regra(regra: Rule) -> String {
+    let descricao = match regra {
+        Rule::expressao | Rule::atribuicao | Rule::binaria | Rule::expressao_aninhada | Rule::atribuicao_aninhada
+        | Rule::binaria_aninhada | Rule::fator | Rule::agrupamento | Rule::op_negativo
+        | Rule::op_negacao | Rule::novo_modelo | Rule::literal_modelo | Rule::funcao_anonima
+        | Rule::fatia => "uma expressão",
+        Rule::indice => "`[`",
+        Rule::acesso => "`.`",
+        Rule::argumentos => "`(`",
+        Rule::op_fatia => "`..`",
+        Rule::texto_literal | Rule::texto_simples | Rule::texto_multilinha
+        | Rule::texto_interpolado | Rule::texto_parte => "um texto",
+        Rule::interpolacao => "`${`",
+        Rule::numero_literal => "um número",
+        Rule::identificador => "um identificador",
+        Rule::tipo | Rule::tipo_lista | Rule::tipo_mapa | Rule::tipo_generico | Rule::tipo_funcao
+        | Rule::tipos_parametros => "um tipo",
+        Rule::parametros_tipo => "`<`",
+        Rule::bloco => "`{`",
+        Rule::valores_campos => "`{`",
+        Rule::valor_campo => "um campo",
+        Rule::eu => "`eu`",
+        Rule::metodo | Rule::assinatura => "um método",
+        Rule::contratos_cumpridos => "`cumpre`",
+        Rule::passo => "`passo`",
+        Rule::caso => "`caso`",
+        Rule::padrao => "`padrão`",
+        Rule::parametros | Rule::parametro => "um parâmetro",
+        Rule::campo => "um campo",
+        Rule::variante => "uma variante",
+        Rule::ponto_e_virgula => "`;`",
+        Rule::virgula => "`,`",
+        Rule::fim_linha => "uma quebra de linha",
+        Rule::padrao_variante => "uma expressão",
+        Rule::caminho_modulo => "o nome de um módulo",
+        Rule::op_atribuicao => "`=`",
+        Rule::op_ou | Rule::op_e | Rule::op_comparacao | Rule::op_soma | Rule::op_termo => "um operador",
+        Rule::EOI => "o fim do arquivo",
+        _ => "uma declaração",
+    };
+    descricao.to_string()
+}
+
+/// Símbolos esperados pelo pest que não ajudam na mensagem: espaços,
+/// comentários, classes de caracteres, a continuação de números e
+/// identificadores e operadores que continuariam a expressão anterior
+fn simbolo_ignorado(token: &str) -> bool {
+    const OPERADORES: &[&str] = &[
+        "=", "+=", "-=", "*=", "/=", "%=", "+", "-", "*", "/", "%", "resto", "e", "ou",
+        ">", ">=", "<", "<=", "==", "!=", "é", "em", "[",
+    ];
+    token.trim().is_empty()
+        || ["//", "/*", "BUILTIN_RULE", "_", ".", "E"].contains(&token)
+        || token.contains("..")
+        || OPERADORES.contains(&token)
+}
+
+fn adicionar_sem_repetir(lista: &mut Vec<String>, item: String) {
+    if !lista.contains(&item) {
+        lista.push(item);
+    }
+}
+
+/// Primeiro token do código, como escrito; vazio no fim do arquivo
+fn trecho_encontrado(codigo: &str) -> &str {
+    let mut lexer = Lexer::new(codigo);
+    match lexer.proximo_token_com_trivia() {
+        Ok((_, token)) if token.token != Token::EOF => &codigo[token.inicio..token.fim],
+        _ => {
+            let restante = codigo.trim_start();
+            restante.chars().next().map_or("", |c| &restante[..c.len_utf8()])
+        }
+    }
+}
+
+/// Converte uma lista de tokens em uma AST.
+///
+/// Parser legado: só entende `mostre`, `faça`, atribuições e chamadas no
+/// nível superior, e pula o que não reconhece. Fica disponível para
+/// ferramentas baseadas em tokens; para esse subconjunto, produz a mesma AST
+/// que `analisar_codigo`.
+pub fn analisar(tokens: Vec<TokenComPosicao>) -> Result<Programa> {
+    // Linha e coluna de cada token, para as mensagens de erro
+    let posicoes: Vec<(usize, usize)> = tokens.iter().map(|t| (t.linha, t.coluna)).collect();
+    let tokens: Vec<Token> = tokens.into_iter().map(|t| t.token).collect();
     let mut programa = Programa::new();
+    // Linhas de comentários `///` aguardando a próxima função
+    let mut documentacao: Vec<String> = Vec::new();
     
     let mut i = 0;
     while i < tokens.len() && tokens[i] != Token::EOF {
+        if !matches!(tokens[i], Token::ComentarioDoc(_) | Token::Faca) {
+            documentacao.clear();
+        }
+        
         match &tokens[i] {
+            Token::ComentarioDoc(linha) => {
+                documentacao.push(linha.to_string());
+                i += 1;
+            },
+            
             Token::Mostre => {
                 i += 1; // Avançar depois de 'mostre'
                 
                 if i < tokens.len() {
                     // Pode ser uma string literal simples ou uma expressão de concatenação
-                    let expr = analisar_expressao(&mut i, &tokens)?;
+                    let expr = analisar_expressao(&mut i, &tokens, &posicoes)?;
                     programa.adicionar_declaracao(Declaracao::Mostrar(expr));
                 } else {
-                    return Err(anyhow::anyhow!("Expressão incompleta após 'mostre'"));
+                    return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["uma expressão"]));
                 }
             },
             
@@ -36,12 +376,12 @@ pub fn analisar(tokens: Vec<Token>) -> Result<Programa> {
                 // Extrai o nome da função
                 let nome = if i < tokens.len() {
                     if let Token::Identificador(nome) = &tokens[i] {
-                        nome.clone()
+                        nome.to_string()
                     } else {
-                        return Err(anyhow::anyhow!("Esperava nome de função após 'faça'"));
+                        return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["o nome da função"]));
                     }
                 } else {
-                    return Err(anyhow::anyhow!("Expressão incompleta após 'faça'"));
+                    return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["o nome da função"]));
                 };
                 
                 i += 1; // Avançar depois do nome
@@ -62,12 +402,13 @@ pub fn analisar(tokens: Vec<Token>) -> Result<Programa> {
                                 let tipo = match &tokens[i] {
                                     Token::TipoTexto => Tipo::Texto,
                                     Token::TipoNumero => Tipo::Numero,
+                                    Token::TipoInteiro => Tipo::Inteiro,
                                     Token::TipoLogico => Tipo::Logico,
-                                    Token::Identificador(nome_tipo) => Tipo::Personalizado(nome_tipo.clone()),
-                                    _ => return Err(anyhow::anyhow!("Tipo de parâmetro inválido")),
+                                    Token::Identificador(nome_tipo) => Tipo::Personalizado(nome_tipo.to_string()),
+                                    _ => return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["um tipo"])),
                                 };
                                 
-                                parametros.push((nome_param.clone(), tipo));
+                                parametros.push((nome_param.to_string(), tipo));
                                 i += 1; // Avançar depois do tipo
                                 
                                 // Verifica se há uma vírgula
@@ -75,16 +416,16 @@ pub fn analisar(tokens: Vec<Token>) -> Result<Programa> {
                                     i += 1; // Avançar depois da vírgula
                                 }
                             } else {
-                                return Err(anyhow::anyhow!("Esperava ':' após nome do parâmetro"));
+                                return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["`:`"]));
                             }
                         } else {
-                            return Err(anyhow::anyhow!("Parâmetro de função inválido"));
+                            return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["um parâmetro", "`)`"]));
                         }
                     }
                     
                     i += 1; // Avançar depois de ')'
                 } else {
-                    return Err(anyhow::anyhow!("Esperava '(' após nome da função"));
+                    return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["`(`"]));
                 }
                 
                 // Corpo da função
@@ -104,19 +445,23 @@ pub fn analisar(tokens: Vec<Token>) -> Result<Programa> {
                                         let expr = Expressao::NumeroLiteral(valor);
                                         declaracoes_corpo.push(Declaracao::Retorno(Some(expr)));
                                         i += 1;
+                                    } else if let Token::InteiroLiteral(valor) = tokens[i] {
+                                        let expr = Expressao::InteiroLiteral(valor);
+                                        declaracoes_corpo.push(Declaracao::Retorno(Some(expr)));
+                                        i += 1;
                                     } else if let Token::TextoLiteral(texto) = &tokens[i] {
-                                        let expr = Expressao::TextoLiteral(texto.clone());
+                                        let expr = Expressao::TextoLiteral(texto.to_string());
                                         declaracoes_corpo.push(Declaracao::Retorno(Some(expr)));
                                         i += 1;
                                     } else if let Token::Identificador(id) = &tokens[i] {
-                                        let expr = Expressao::Identificador(id.clone());
+                                        let expr = Expressao::Identificador(id.to_string());
                                         declaracoes_corpo.push(Declaracao::Retorno(Some(expr)));
                                         i += 1;
                                     } else {
-                                        return Err(anyhow::anyhow!("Expressão de retorno inválida"));
+                                        return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["uma expressão"]));
                                     }
                                 } else {
-                                    return Err(anyhow::anyhow!("Expressão incompleta após 'volte'"));
+                                    return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["uma expressão"]));
                                 }
                             },
                             
@@ -125,13 +470,21 @@ pub fn analisar(tokens: Vec<Token>) -> Result<Programa> {
                                 
                                 if i < tokens.len() {
                                     // Pode ser uma string literal simples ou uma expressão de concatenação
-                                    let expr = analisar_expressao(&mut i, &tokens)?;
+                                    let expr = analisar_expressao(&mut i, &tokens, &posicoes)?;
                                     declaracoes_corpo.push(Declaracao::Mostrar(expr));
                                 } else {
-                                    return Err(anyhow::anyhow!("Expressão incompleta após 'mostre'"));
+                                    return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["uma expressão"]));
                                 }
                             },
                             
+                            Token::Identificador(nome) if tokens.get(i + 1).is_some_and(eh_atribuicao) => {
+                                let nome = nome.to_string();
+                                i += 1; // Avançar depois do nome
+                                
+                                let expr = analisar_atribuicao(nome, &mut i, &tokens, &posicoes)?;
+                                declaracoes_corpo.push(Declaracao::Expressao(expr));
+                            },
+                            
                             // Outros tipos de declarações no corpo...
                             _ => {
                                 // Simplificado: pular tokens desconhecidos
@@ -145,19 +498,30 @@ pub fn analisar(tokens: Vec<Token>) -> Result<Programa> {
                     let corpo = Declaracao::Bloco(declaracoes_corpo);
                     programa.adicionar_declaracao(Declaracao::Funcao {
                         nome,
+                        genericos: Vec::new(),
                         parametros,
                         tipo_retorno: None, // Simplificado
                         corpo: Box::new(corpo),
                         publico: false,
+                        documentacao: juntar_documentacao(std::mem::take(&mut documentacao)),
                     });
                 } else {
-                    return Err(anyhow::anyhow!("Esperava '{{' para o corpo da função"));
+                    return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["`{`"]));
                 }
             },
             
+            // Atribuição (`x = 1`, `x += 1`)
+            Token::Identificador(nome) if tokens.get(i + 1).is_some_and(eh_atribuicao) => {
+                let nome = nome.to_string();
+                i += 1; // Avançar depois do nome
+                
+                let expr = analisar_atribuicao(nome, &mut i, &tokens, &posicoes)?;
+                programa.adicionar_declaracao(Declaracao::Expressao(expr));
+            },
+            
             // Chamada de função
             Token::Identificador(nome) => {
-                let nome_func = nome.clone();
+                let nome_func = nome.to_string();
                 i += 1; // Avançar depois do nome da função
                 
                 if i < tokens.len() && tokens[i] == Token::AbreParentese {
@@ -168,7 +532,7 @@ pub fn analisar(tokens: Vec<Token>) -> Result<Programa> {
                     // Analisar argumentos
                     while i < tokens.len() && tokens[i] != Token::FechaParentese {
                         // Analisar o argumento (que pode ser uma expressão)
-                        let arg = analisar_expressao(&mut i, &tokens)?;
+                        let arg = analisar_expressao(&mut i, &tokens, &posicoes)?;
                         argumentos.push(arg);
                         
                         // Verificar se há uma vírgula
@@ -187,10 +551,10 @@ pub fn analisar(tokens: Vec<Token>) -> Result<Programa> {
                         
                         programa.adicionar_declaracao(Declaracao::Expressao(expr));
                     } else {
-                        return Err(anyhow::anyhow!("Esperava ')' após argumentos da função"));
+                        return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["`)`"]));
                     }
                 } else {
-                    return Err(anyhow::anyhow!("Esperava '(' após nome da função"));
+                    return Err(ErroSintatico::em_token(&tokens, &posicoes, i, &["`(`", "`=`"]));
                 }
             },
             
@@ -205,10 +569,62 @@ pub fn analisar(tokens: Vec<Token>) -> Result<Programa> {
     Ok(programa)
 }
 
+/// Nome de um identificador, normalizado em NFC como no lexer
+fn nome_identificador(pair: &Pair<Rule>) -> String {
+    normalizar_identificador(pair.as_str()).into_owned()
+}
+
+fn operacao(operador: Operador, esquerda: Expressao, direita: Expressao) -> Expressao {
+    Expressao::Operacao {
+        operador,
+        esquerda: Box::new(esquerda),
+        direita: Box::new(direita),
+    }
+}
+
+fn operador_binario(op: &str) -> Result<Operador> {
+    Ok(match op {
+        "ou" => Operador::Ou,
+        "e" => Operador::E,
+        ">" => Operador::Maior,
+        ">=" => Operador::MaiorIgual,
+        "<" => Operador::Menor,
+        "<=" => Operador::MenorIgual,
+        "==" => Operador::Igual,
+        "!=" => Operador::Diferente,
+        // `é igual a` pode ter qualquer espaçamento entre as palavras
+        op if op.starts_with('é') => Operador::Igual,
+        "em" => Operador::Contem,
+        "+" => Operador::Soma,
+        "-" => Operador::Subtracao,
+        "*" => Operador::Multiplicacao,
+        "/" => Operador::Divisao,
+        "resto" | "%" => Operador::Resto,
+        _ => return Err(anyhow!("Operador desconhecido: {}", op)),
+    })
+}
+
+/// Comparações de ordem, as únicas que se encadeiam
+fn eh_relacional(operador: &Operador) -> bool {
+    matches!(operador, Operador::Maior | Operador::Menor | Operador::MaiorIgual | Operador::MenorIgual)
+}
+
+/// Junta as linhas de comentários `///` em um único texto
+fn juntar_documentacao(linhas: Vec<String>) -> Option<String> {
+    if linhas.is_empty() {
+        None
+    } else {
+        Some(linhas.join("\n"))
+    }
+}
+
 // Implementação completa usando pest
 pub fn analisar_com_pest(codigo: &str) -> Result<Programa> {
     let resultado = PBRParser::parse(Rule::programa, codigo)
-        .with_context(|| "Erro de análise de gramática PBRLang")?;
+        .map_err(|erro| {
+            let (_, erros_lexicos) = tokenizar_com_recuperacao(codigo);
+            ErroSintatico::de_pest(codigo, 0, erro, &numeros_invalidos(&erros_lexicos))
+        })?;
     
     // O programa é o primeiro par da análise
     if let Some(programa_par) = resultado.peek() {
@@ -242,24 +658,69 @@ impl PBRParser {
     }
     
     fn parse_declaracao(pair: Pair<Rule>) -> Result<Declaracao> {
-        let inner = pair.into_inner().next()
+        let mut pares = pair.into_inner();
+        
+        // Comentários `///` antes da declaração
+        let mut documentacao = None;
+        if let Some(doc) = pares.peek().filter(|p| p.as_rule() == Rule::documentacao) {
+            pares.next();
+            documentacao = Self::parse_documentacao(doc);
+        }
+        
+        let inner = pares.next()
             .ok_or_else(|| anyhow!("Declaração vazia"))?;
         
+        let mut declaracao = Self::parse_declaracao_sem_documentacao(inner)?;
+        
+        // A documentação só é guardada em funções, métodos, modelos,
+        // enumerações e contratos
+        if let Declaracao::Funcao { documentacao: doc, .. }
+             | Declaracao::Metodo { documentacao: doc, .. }
+             | Declaracao::Modelo { documentacao: doc, .. }
+             | Declaracao::Enumeracao { documentacao: doc, .. }
+             | Declaracao::Contrato { documentacao: doc, .. } = &mut declaracao
+        {
+            *doc = documentacao;
+        }
+        
+        Ok(declaracao)
+    }
+    
+    fn parse_documentacao(pair: Pair<Rule>) -> Option<String> {
+        juntar_documentacao(
+            pair.into_inner()
+                .map(|linha| texto_documentacao(linha.as_str()).to_string())
+                .collect(),
+        )
+    }
+    
+    fn parse_declaracao_sem_documentacao(inner: Pair<Rule>) -> Result<Declaracao> {
         match inner.as_rule() {
             Rule::declaracao_variavel => Self::parse_declaracao_variavel(inner),
             Rule::declaracao_funcao => Self::parse_declaracao_funcao(inner),
+            Rule::declaracao_metodo => Self::parse_metodo(inner, None),
             Rule::declaracao_retorno => Self::parse_declaracao_retorno(inner),
             Rule::declaracao_condicional => Self::parse_declaracao_condicional(inner),
+            Rule::declaracao_escolha => Self::parse_declaracao_escolha(inner),
             Rule::declaracao_para_cada => Self::parse_declaracao_para_cada(inner),
+            Rule::declaracao_para_cada_em => Self::parse_declaracao_para_cada_em(inner),
             Rule::declaracao_enquanto => Self::parse_declaracao_enquanto(inner),
             Rule::declaracao_repita => Self::parse_declaracao_repita(inner),
             Rule::declaracao_pare => Ok(Declaracao::Pare),
             Rule::declaracao_continue => Ok(Declaracao::Continue),
             Rule::declaracao_quando_der_erro => Self::parse_declaracao_quando_der_erro(inner),
             Rule::declaracao_modelo => Self::parse_declaracao_modelo(inner),
+            Rule::declaracao_enumeracao => Self::parse_declaracao_enumeracao(inner),
+            Rule::declaracao_contrato => Self::parse_declaracao_contrato(inner),
             Rule::declaracao_modulo => Self::parse_declaracao_modulo(inner),
             Rule::declaracao_importar => Self::parse_declaracao_importar(inner),
             Rule::declaracao_mostrar => Self::parse_declaracao_mostrar(inner),
+            // O separador depois da declaração não entra na árvore
+            Rule::declaracao_simples => {
+                let declaracao = inner.into_inner().next()
+                    .ok_or_else(|| anyhow!("Declaração simples vazia"))?;
+                Self::parse_declaracao_sem_documentacao(declaracao)
+            },
             Rule::expressao => {
                 let expr = Self::parse_expressao(inner)?;
                 Ok(Declaracao::Expressao(expr))
@@ -283,12 +744,12 @@ impl PBRParser {
         let identificador = inner.next()
             .ok_or_else(|| anyhow!("Identificador não encontrado na declaração de variável"))?;
         
-        let nome = identificador.as_str().to_string();
+        let nome = nome_identificador(&identificador);
         let mut tipo = None;
         let mut valor = None;
         
         // Verifica se há tipo e valor
-        while let Some(next) = inner.next() {
+        for next in inner {
             match next.as_rule() {
                 Rule::tipo => {
                     tipo = Some(Self::parse_tipo(next)?);
@@ -320,22 +781,28 @@ impl PBRParser {
         // Pega o nome da função
         let nome_par = inner.next()
             .ok_or_else(|| anyhow!("Nome da função não encontrado"))?;
-        let nome = nome_par.as_str().to_string();
+        let nome = nome_identificador(&nome_par);
         
-        // Pega os parâmetros
-        let params_par = inner.next()
-            .ok_or_else(|| anyhow!("Parâmetros da função não encontrados"))?;
-        let parametros = Self::parse_parametros(params_par)?;
+        let mut genericos = Vec::new();
+        if let Some(genericos_par) = inner.peek().filter(|p| p.as_rule() == Rule::parametros_tipo) {
+            inner.next(); // Consome os parâmetros de tipo
+            genericos = genericos_par.into_inner().map(|p| nome_identificador(&p)).collect();
+        }
+        
+        // Pega os parâmetros (a regra `parametros` não aparece em `f()`)
+        let mut parametros = Vec::new();
+        if let Some(params_par) = inner.peek().filter(|p| p.as_rule() == Rule::parametros) {
+            inner.next(); // Consome os parâmetros
+            parametros = Self::parse_parametros(params_par)?;
+        }
         
         // Verifica se há tipo de retorno
         let mut tipo_retorno = None;
         let proximo = inner.peek();
         
-        if let Some(next) = proximo {
-            if next.as_rule() == Rule::tipo {
-                inner.next(); // Consome o tipo
-                tipo_retorno = Some(Self::parse_tipo(next)?);
-            }
+        if let Some(next) = proximo.filter(|p| p.as_rule() == Rule::tipo) {
+            inner.next(); // Consome o tipo
+            tipo_retorno = Some(Self::parse_tipo(next)?);
         }
         
         // Pega o corpo da função
@@ -345,10 +812,12 @@ impl PBRParser {
         
         Ok(Declaracao::Funcao {
             nome,
+            genericos,
             parametros,
             tipo_retorno,
             corpo: Box::new(corpo),
             publico,
+            documentacao: None,
         })
     }
     
@@ -361,7 +830,7 @@ impl PBRParser {
                 
                 let nome_par = inner.next()
                     .ok_or_else(|| anyhow!("Nome do parâmetro não encontrado"))?;
-                let nome = nome_par.as_str().to_string();
+                let nome = nome_identificador(&nome_par);
                 
                 let tipo_par = inner.next()
                     .ok_or_else(|| anyhow!("Tipo do parâmetro não encontrado"))?;
@@ -375,13 +844,48 @@ impl PBRParser {
     }
     
     fn parse_tipo(pair: Pair<Rule>) -> Result<Tipo> {
-        let tipo_str = pair.as_str();
+        // Tipos compostos têm um par interno; os demais, só o nome
+        if let Some(composto) = pair.clone().into_inner().next()
+            .filter(|p| p.as_rule() != Rule::identificador)
+        {
+            let regra = composto.as_rule();
+            let mut partes = composto.into_inner();
+            return match regra {
+                Rule::tipo_lista => {
+                    let item = partes.next().ok_or_else(|| anyhow!("Tipo dos itens da lista não encontrado"))?;
+                    Ok(Tipo::Lista(Box::new(Self::parse_tipo(item)?)))
+                },
+                Rule::tipo_mapa => {
+                    let primeiro = partes.next().ok_or_else(|| anyhow!("Tipo dos valores do mapa não encontrado"))?;
+                    let primeiro = Self::parse_tipo(primeiro)?;
+                    match partes.next() {
+                        Some(valor) => Ok(Tipo::Mapa(Box::new(primeiro), Box::new(Self::parse_tipo(valor)?))),
+                        None => Ok(Tipo::Mapa(Box::new(Tipo::Texto), Box::new(primeiro))),
+                    }
+                },
+                Rule::tipo_generico => {
+                    let nome_par = partes.next().ok_or_else(|| anyhow!("Nome do tipo não encontrado"))?;
+                    let argumentos = partes.map(Self::parse_tipo).collect::<Result<Vec<_>>>()?;
+                    Ok(Tipo::Generico(nome_identificador(&nome_par), argumentos))
+                },
+                Rule::tipo_funcao => {
+                    let tipos_par = partes.next().ok_or_else(|| anyhow!("Parâmetros do tipo função não encontrados"))?;
+                    let parametros = tipos_par.into_inner().map(Self::parse_tipo).collect::<Result<Vec<_>>>()?;
+                    let retorno = partes.next().map(Self::parse_tipo).transpose()?;
+                    Ok(Tipo::Funcao(parametros, retorno.map(Box::new)))
+                },
+                _ => Err(anyhow!("Tipo desconhecido: {:?}", regra)),
+            };
+        }
         
-        match tipo_str {
+        let tipo_str = normalizar_identificador(pair.as_str()).into_owned();
+        
+        match tipo_str.as_str() {
             "texto" => Ok(Tipo::Texto),
             "número" | "numero" => Ok(Tipo::Numero),
+            "inteiro" => Ok(Tipo::Inteiro),
             "lógico" | "logico" => Ok(Tipo::Logico),
-            _ => Ok(Tipo::Personalizado(tipo_str.to_string())),
+            _ => Ok(Tipo::Personalizado(tipo_str)),
         }
     }
     
@@ -422,9 +926,13 @@ impl PBRParser {
             .ok_or_else(|| anyhow!("Bloco 'se' não encontrado na declaração condicional"))?;
         let bloco_se = Self::parse_bloco(bloco_se_par)?;
         
+        // `senão se` encadeia outra condicional no lugar do bloco
         let mut bloco_senao = None;
         if let Some(senao_par) = inner.next() {
-            let senao = Self::parse_bloco(senao_par)?;
+            let senao = match senao_par.as_rule() {
+                Rule::declaracao_condicional => Self::parse_declaracao_condicional(senao_par)?,
+                _ => Self::parse_bloco(senao_par)?,
+            };
             bloco_senao = Some(Box::new(senao));
         }
         
@@ -435,12 +943,54 @@ impl PBRParser {
         })
     }
     
+    fn parse_declaracao_escolha(pair: Pair<Rule>) -> Result<Declaracao> {
+        let mut inner = pair.into_inner();
+        
+        let valor_par = inner.next()
+            .ok_or_else(|| anyhow!("Valor não encontrado na declaração escolha"))?;
+        let valor = Self::parse_expressao(valor_par)?;
+        
+        let mut casos = Vec::new();
+        let mut padrao = None;
+        for par in inner {
+            match par.as_rule() {
+                Rule::caso => {
+                    let mut valores = Vec::new();
+                    let mut bloco = None;
+                    for parte in par.into_inner() {
+                        match parte.as_rule() {
+                            Rule::bloco => bloco = Some(Self::parse_bloco(parte)?),
+                            Rule::padrao_variante => {
+                                let mut nomes = parte.into_inner().map(|p| nome_identificador(&p));
+                                let nome = nomes.next()
+                                    .ok_or_else(|| anyhow!("Nome da variante não encontrado no caso"))?;
+                                valores.push(Padrao::Variante { nome, campos: nomes.collect() });
+                            },
+                            _ => valores.push(Padrao::Valor(Self::parse_expressao(parte)?)),
+                        }
+                    }
+                    let bloco = bloco
+                        .ok_or_else(|| anyhow!("Bloco não encontrado no caso"))?;
+                    casos.push((valores, bloco));
+                },
+                Rule::padrao => {
+                    let bloco_par = par.into_inner().next()
+                        .ok_or_else(|| anyhow!("Bloco não encontrado no padrão"))?;
+                    padrao = Some(Box::new(Self::parse_bloco(bloco_par)?));
+                },
+                _ => return Err(anyhow!("Regra inesperada na declaração escolha: {:?}", par.as_rule())),
+            }
+        }
+        
+        Ok(Declaracao::Escolha { valor, casos, padrao })
+    }
+    
     fn parse_declaracao_para_cada(pair: Pair<Rule>) -> Result<Declaracao> {
         let mut inner = pair.into_inner();
         
         let var_par = inner.next()
             .ok_or_else(|| anyhow!("Variável não encontrada no loop para cada"))?;
-        let variavel = var_par.as_str().to_string();
+        let variavel = nome_identificador(&var_par);
         
         let inicio_par = inner.next()
             .ok_or_else(|| anyhow!("Expressão de início não encontrada no loop para cada"))?;
@@ -450,18 +1000,57 @@ impl PBRParser {
             .ok_or_else(|| anyhow!("Expressão de fim não encontrada no loop para cada"))?;
         let fim = Self::parse_expressao(fim_par)?;
         
-        let corpo_par = inner.next()
+        let mut corpo_par = inner.next()
             .ok_or_else(|| anyhow!("Corpo não encontrado no loop para cada"))?;
+        
+        let mut passo = None;
+        if corpo_par.as_rule() == Rule::passo {
+            let passo_par = corpo_par.into_inner().next()
+                .ok_or_else(|| anyhow!("Expressão do passo não encontrada no loop para cada"))?;
+            passo = Some(Self::parse_expressao(passo_par)?);
+            corpo_par = inner.next()
+                .ok_or_else(|| anyhow!("Corpo não encontrado no loop para cada"))?;
+        }
         let corpo = Self::parse_bloco(corpo_par)?;
         
         Ok(Declaracao::ParaCada {
             variavel,
             inicio,
             fim,
+            passo,
             corpo: Box::new(corpo),
         })
     }
     
+    fn parse_declaracao_para_cada_em(pair: Pair<Rule>) -> Result<Declaracao> {
+        let mut inner = pair.into_inner();
+        
+        let var_par = inner.next()
+            .ok_or_else(|| anyhow!("Variável não encontrada no loop para cada"))?;
+        
+        let colecao_par = inner.next()
+            .ok_or_else(|| anyhow!("Coleção não encontrada no loop para cada"))?;
+        let colecao = Self::parse_expressao(colecao_par)?;
+        
+        let corpo_par = inner.next()
+            .ok_or_else(|| anyhow!("Corpo não encontrado no loop para cada"))?;
+        let corpo = Box::new(Self::parse_bloco(corpo_par)?);
+        
+        if var_par.as_rule() == Rule::par_variaveis {
+            let mut nomes = var_par.into_inner().map(|par| nome_identificador(&par));
+            let (Some(chave), Some(valor)) = (nomes.next(), nomes.next()) else {
+                return Err(anyhow!("Par de variáveis incompleto no loop para cada"));
+            };
+            return Ok(Declaracao::ParaCadaPar { chave, valor, colecao, corpo });
+        }
+        
+        Ok(Declaracao::ParaCadaEm {
+            variavel: nome_identificador(&var_par),
+            colecao,
+            corpo,
+        })
+    }
+    
     fn parse_declaracao_enquanto(pair: Pair<Rule>) -> Result<Declaracao> {
         let mut inner = pair.into_inner();
         
@@ -505,7 +1094,7 @@ impl PBRParser {
         
         let var_erro_par = inner.next()
             .ok_or_else(|| anyhow!("Variável de erro não encontrada no tratamento de erro"))?;
-        let variavel_erro = var_erro_par.as_str().to_string();
+        let variavel_erro = nome_identificador(&var_erro_par);
         
         let bloco_catch_par = inner.next()
             .ok_or_else(|| anyhow!("Bloco catch não encontrado no tratamento de erro"))?;
@@ -531,13 +1120,22 @@ impl PBRParser {
         
         let nome_par = inner.next()
             .ok_or_else(|| anyhow!("Nome do modelo não encontrado"))?;
-        let nome = nome_par.as_str().to_string();
+        let nome = nome_identificador(&nome_par);
         
+        let mut genericos = Vec::new();
         let mut campos = Vec::new();
+        let mut metodos = Vec::new();
+        let mut contratos = Vec::new();
         
-        // Processa todos os campos
+        // Processa os parâmetros de tipo, os contratos, os campos e os métodos
         for campo in inner {
-            if campo.as_rule() == Rule::campo {
+            if campo.as_rule() == Rule::parametros_tipo {
+                genericos = campo.into_inner().map(|p| nome_identificador(&p)).collect();
+            } else if campo.as_rule() == Rule::contratos_cumpridos {
+                contratos = campo.into_inner().map(|p| nome_identificador(&p)).collect();
+            } else if campo.as_rule() == Rule::metodo {
+                metodos.push(Self::parse_metodo(campo, Some(&nome))?);
+            } else if campo.as_rule() == Rule::campo {
                 let mut campo_inner = campo.into_inner();
                 let mut campo_publico = false;
                 
@@ -551,7 +1149,7 @@ impl PBRParser {
                 
                 let nome_campo_par = campo_inner.next()
                     .ok_or_else(|| anyhow!("Nome do campo não encontrado"))?;
-                let nome_campo = nome_campo_par.as_str().to_string();
+                let nome_campo = nome_identificador(&nome_campo_par);
                 
                 let tipo_campo_par = campo_inner.next()
                     .ok_or_else(|| anyhow!("Tipo do campo não encontrado"))?;
@@ -561,7 +1159,118 @@ impl PBRParser {
             }
         }
         
-        Ok(Declaracao::Modelo { nome, campos, publico })
+        Ok(Declaracao::Modelo { nome, genericos, campos, metodos, contratos, publico, documentacao: None })
+    }
+    
+    fn parse_declaracao_contrato(pair: Pair<Rule>) -> Result<Declaracao> {
+        let mut inner = pair.into_inner();
+        let mut publico = false;
+        
+        // Verifica se há modificador de visibilidade
+        let primeiro = inner.peek().ok_or_else(|| anyhow!("Declaração de contrato vazia"))?;
+        if primeiro.as_rule() == Rule::modificador_visibilidade {
+            publico = true;
+            inner.next(); // Consome o modificador
+        }
+        
+        let nome_par = inner.next()
+            .ok_or_else(|| anyhow!("Nome do contrato não encontrado"))?;
+        let nome = nome_identificador(&nome_par);
+        
+        let mut metodos = Vec::new();
+        for assinatura in inner {
+            let mut nome_metodo = None;
+            let mut parametros = Vec::new();
+            let mut tipo_retorno = None;
+            let mut documentacao = None;
+            
+            for parte in assinatura.into_inner() {
+                match parte.as_rule() {
+                    Rule::documentacao => documentacao = Self::parse_documentacao(parte),
+                    Rule::identificador => nome_metodo = Some(nome_identificador(&parte)),
+                    Rule::eu => {},
+                    Rule::parametros => parametros = Self::parse_parametros(parte)?,
+                    Rule::tipo => tipo_retorno = Some(Self::parse_tipo(parte)?),
+                    _ => return Err(anyhow!("Regra inesperada no contrato: {:?}", parte.as_rule())),
+                }
+            }
+            
+            metodos.push(Assinatura {
+                nome: nome_metodo.ok_or_else(|| anyhow!("Nome do método não encontrado no contrato"))?,
+                parametros,
+                tipo_retorno,
+                documentacao,
+            });
+        }
+        
+        Ok(Declaracao::Contrato { nome, metodos, publico, documentacao: None })
+    }
+    
+    /// Método declarado dentro de `modelo`, ou fora dele com o nome do
+    /// modelo antes do ponto (`modelo` é `None`)
+    fn parse_metodo(pair: Pair<Rule>, modelo: Option<&str>) -> Result<Declaracao> {
+        let mut modelo = modelo.map(str::to_string);
+        let mut nome = None;
+        let mut parametros = Vec::new();
+        let mut tipo_retorno = None;
+        let mut corpo = None;
+        let mut publico = false;
+        let mut documentacao = None;
+        
+        for parte in pair.into_inner() {
+            match parte.as_rule() {
+                Rule::documentacao => documentacao = Self::parse_documentacao(parte),
+                Rule::modificador_visibilidade => publico = true,
+                // Fora do modelo, o primeiro nome é o do modelo
+                Rule::identificador if modelo.is_none() => modelo = Some(nome_identificador(&parte)),
+                Rule::identificador => nome = Some(nome_identificador(&parte)),
+                Rule::eu => {},
+                Rule::parametros => parametros = Self::parse_parametros(parte)?,
+                Rule::tipo => tipo_retorno = Some(Self::parse_tipo(parte)?),
+                Rule::bloco => corpo = Some(Self::parse_bloco(parte)?),
+                _ => return Err(anyhow!("Regra inesperada no método: {:?}", parte.as_rule())),
+            }
+        }
+        
+        Ok(Declaracao::Metodo {
+            modelo: modelo.ok_or_else(|| anyhow!("Modelo do método não encontrado"))?,
+            nome: nome.ok_or_else(|| anyhow!("Nome do método não encontrado"))?,
+            parametros,
+            tipo_retorno,
+            corpo: Box::new(corpo.ok_or_else(|| anyhow!("Corpo do método não encontrado"))?),
+            publico,
+            documentacao,
+        })
+    }
+    
+    fn parse_declaracao_enumeracao(pair: Pair<Rule>) -> Result<Declaracao> {
+        let mut inner = pair.into_inner();
+        let mut publico = false;
+        
+        // Verifica se há modificador de visibilidade
+        let primeiro = inner.peek().ok_or_else(|| anyhow!("Declaração de tipo vazia"))?;
+        if primeiro.as_rule() == Rule::modificador_visibilidade {
+            publico = true;
+            inner.next(); // Consome o modificador
+        }
+        
+        let nome_par = inner.next()
+            .ok_or_else(|| anyhow!("Nome do tipo não encontrado"))?;
+        let nome = nome_identificador(&nome_par);
+        
+        let mut variantes = Vec::new();
+        for variante in inner {
+            let mut variante_inner = variante.into_inner();
+            let nome_variante_par = variante_inner.next()
+                .ok_or_else(|| anyhow!("Nome da variante não encontrado"))?;
+            let campos = match variante_inner.next().filter(|p| p.as_rule() == Rule::parametros) {
+                Some(parametros) => Self::parse_parametros(parametros)?,
+                None => Vec::new(),
+            };
+            variantes.push((nome_identificador(&nome_variante_par), campos));
+        }
+        
+        Ok(Declaracao::Enumeracao { nome, variantes, publico, documentacao: None })
     }
     
     fn parse_declaracao_modulo(pair: Pair<Rule>) -> Result<Declaracao> {
@@ -569,7 +1278,7 @@ impl PBRParser {
         
         let nome_par = inner.next()
             .ok_or_else(|| anyhow!("Nome do módulo não encontrado"))?;
-        let nome = nome_par.as_str().to_string();
+        let nome = nome_identificador(&nome_par);
         
         let bloco_par = inner.next()
             .ok_or_else(|| anyhow!("Bloco do módulo não encontrado"))?;
@@ -593,7 +1302,7 @@ impl PBRParser {
         let mut caminho = Vec::new();
         
         for parte in caminho_par.into_inner() {
-            caminho.push(parte.as_str().to_string());
+            caminho.push(nome_identificador(&parte));
         }
         
         Ok(Declaracao::Importar { caminho })
@@ -610,18 +1319,14 @@ impl PBRParser {
     
     fn parse_expressao(pair: Pair<Rule>) -> Result<Expressao> {
         match pair.as_rule() {
-            Rule::expressao => {
+            Rule::expressao | Rule::expressao_aninhada => {
                 // Passar para o próximo nível (atribuição)
                 let inner = pair.into_inner().next()
                     .ok_or_else(|| anyhow!("Expressão vazia"))?;
                 Self::parse_expressao(inner)
             },
-            Rule::atribuicao => Self::parse_atribuicao(pair),
-            Rule::or_expr => Self::parse_or_expr(pair),
-            Rule::and_expr => Self::parse_and_expr(pair),
-            Rule::comparacao => Self::parse_comparacao(pair),
-            Rule::soma => Self::parse_soma(pair),
-            Rule::termo => Self::parse_termo(pair),
+            Rule::atribuicao | Rule::atribuicao_aninhada => Self::parse_atribuicao(pair),
+            Rule::binaria | Rule::binaria_aninhada => Self::parse_binaria(pair),
             Rule::fator => Self::parse_fator(pair),
             _ => Err(anyhow!("Tipo de expressão desconhecido: {:?}", pair.as_rule())),
         }
@@ -635,171 +1340,175 @@ impl PBRParser {
         let esquerda = Self::parse_expressao(esquerda_par)?;
         
         // Se houver mais partes, é uma atribuição
-        if let Some(valor_par) = inner.next() {
-            let valor = Self::parse_expressao(valor_par)?;
+        if let Some(op_par) = inner.next() {
+            // Atribuições compostas viram a forma simples: `x += 1` é `x = x + 1`
+            let operador = match op_par.as_str() {
+                "=" => None,
+                "+=" => Some(Operador::Soma),
+                "-=" => Some(Operador::Subtracao),
+                "*=" => Some(Operador::Multiplicacao),
+                "/=" => Some(Operador::Divisao),
+                "%=" => Some(Operador::Resto),
+                _ => return Err(anyhow!("Operador de atribuição desconhecido: {}", op_par.as_str())),
+            };
+            
+            let valor_par = inner.next()
+                .ok_or_else(|| anyhow!("Valor da atribuição não encontrado"))?;
+            let mut valor = Self::parse_expressao(valor_par)?;
             
-            // Se o lado esquerdo for um identificador, é uma atribuição direta
-            if let Expressao::Identificador(nome) = esquerda {
-                return Ok(Expressao::Atribuicao {
+            if let Some(operador) = operador {
+                valor = Expressao::Operacao {
+                    operador,
+                    esquerda: Box::new(esquerda.clone()),
+                    direita: Box::new(valor),
+                };
+            }
+            
+            return match esquerda {
+                // Se o lado esquerdo for um identificador, é uma atribuição direta
+                Expressao::Identificador(nome) => Ok(Expressao::Atribuicao {
                     nome,
                     valor: Box::new(valor),
-                });
-            } else {
-                return Err(anyhow!("Lado esquerdo de atribuição deve ser um identificador"));
-            }
+                }),
+                // Atribuição a um campo de modelo (`pessoa.nome = ...`)
+                Expressao::AcessoMembro { objeto, membro } => Ok(Expressao::AtribuicaoMembro {
+                    objeto,
+                    membro,
+                    valor: Box::new(valor),
+                }),
+                _ => Err(anyhow!("Lado esquerdo de atribuição deve ser um identificador ou um campo")),
+            };
         }
         
         // Se não houver mais partes, é apenas a expressão
         Ok(esquerda)
     }
     
-    fn parse_or_expr(pair: Pair<Rule>) -> Result<Expressao> {
-        let mut inner = pair.into_inner();
-        
-        let primeiro_par = inner.next()
-            .ok_or_else(|| anyhow!("Primeira parte da expressão 'ou' não encontrada"))?;
-        let mut expr = Self::parse_expressao(primeiro_par)?;
-        
-        // Se houver mais partes, são operações 'ou'
-        while let Some(proximo_par) = inner.next() {
-            let direita = Self::parse_expressao(proximo_par)?;
-            
-            expr = Expressao::Operacao {
-                operador: Operador::Ou,
-                esquerda: Box::new(expr),
-                direita: Box::new(direita),
-            };
-        }
-        
-        Ok(expr)
+    /// Aplica a tabela de precedência (`PRECEDENCIA`) a uma sequência de
+    /// operandos e operadores
+    fn parse_binaria(pair: Pair<Rule>) -> Result<Expressao> {
+        // O `bool` marca uma comparação de ordem sem parênteses, que pode
+        // ser encadeada: `a < b < c` compara `a` com `b` e `b` com `c`
+        PRECEDENCIA
+            .map_primary(|fator| Ok((Self::parse_fator(fator)?, false)))
+            .map_prefix(|op, operando: Result<(Expressao, bool)>| {
+                let expressao = Box::new(operando?.0);
+                Ok((match op.as_rule() {
+                    Rule::op_negativo => Expressao::Negativo { expressao },
+                    _ => Expressao::Negacao { expressao },
+                }, false))
+            })
+            .map_infix(|esquerda: Result<(Expressao, bool)>, op, direita: Result<(Expressao, bool)>| {
+                let (esquerda, encadeavel) = esquerda?;
+                let (direita, _) = direita?;
+                let operador = operador_binario(op.as_str())?;
+                
+                let relacional = eh_relacional(&operador);
+                if !(relacional && encadeavel) {
+                    return Ok((operacao(operador, esquerda, direita), relacional));
+                }
+                
+                let comparacao = match esquerda {
+                    Expressao::Comparacao { mut operandos, mut operadores } => {
+                        operandos.push(direita);
+                        operadores.push(operador);
+                        Expressao::Comparacao { operandos, operadores }
+                    },
+                    Expressao::Operacao { operador: anterior, esquerda: primeiro, direita: meio } => Expressao::Comparacao {
+                        operandos: vec![*primeiro, *meio, direita],
+                        operadores: vec![anterior, operador],
+                    },
+                    _ => return Err(anyhow!("Comparação encadeada sem comparação anterior")),
+                };
+                Ok((comparacao, true))
+            })
+            .parse(pair.into_inner())
+            .map(|(expressao, _)| expressao)
     }
     
-    fn parse_and_expr(pair: Pair<Rule>) -> Result<Expressao> {
+    fn parse_fator(pair: Pair<Rule>) -> Result<Expressao> {
         let mut inner = pair.into_inner();
-        
-        let primeiro_par = inner.next()
-            .ok_or_else(|| anyhow!("Primeira parte da expressão 'e' não encontrada"))?;
-        let mut expr = Self::parse_expressao(primeiro_par)?;
-        
-        // Se houver mais partes, são operações 'e'
-        while let Some(proximo_par) = inner.next() {
-            let direita = Self::parse_expressao(proximo_par)?;
-            
-            expr = Expressao::Operacao {
-                operador: Operador::E,
-                esquerda: Box::new(expr),
-                direita: Box::new(direita),
+        let primario = inner.next()
+            .ok_or_else(|| anyhow!("Fator vazio"))?;
+        let mut expr = Self::parse_primario(primario)?;
+        
+        // Sufixos, da esquerda para a direita: `a.b(x)[0]`
+        for sufixo in inner {
+            expr = match sufixo.as_rule() {
+                Rule::indice => Self::parse_indice(expr, sufixo)?,
+                Rule::acesso => {
+                    let membro_par = sufixo.into_inner().next()
+                        .ok_or_else(|| anyhow!("Membro não encontrado no acesso a membro"))?;
+                    Expressao::AcessoMembro {
+                        objeto: Box::new(expr),
+                        membro: nome_identificador(&membro_par),
+                    }
+                },
+                Rule::argumentos => Self::parse_chamada(expr, sufixo)?,
+                _ => return Err(anyhow!("Sufixo desconhecido: {:?}", sufixo.as_rule())),
             };
         }
         
         Ok(expr)
     }
     
-    fn parse_comparacao(pair: Pair<Rule>) -> Result<Expressao> {
-        let mut inner = pair.into_inner();
-        
-        let esquerda_par = inner.next()
-            .ok_or_else(|| anyhow!("Lado esquerdo da comparação não encontrado"))?;
-        let esquerda = Self::parse_expressao(esquerda_par)?;
-        
-        // Se houver mais partes, é uma comparação
-        if let Some(op_par) = inner.next() {
-            let op = match op_par.as_str() {
-                ">" => Operador::Maior,
-                ">=" => Operador::MaiorIgual,
-                "<" => Operador::Menor,
-                "<=" => Operador::MenorIgual,
-                "==" => Operador::Igual,
-                "!=" => Operador::Diferente,
-                "é igual a" => Operador::Igual,
-                "em" => Operador::Contem,
-                _ => return Err(anyhow!("Operador de comparação desconhecido: {}", op_par.as_str())),
-            };
-            
-            let direita_par = inner.next()
-                .ok_or_else(|| anyhow!("Lado direito da comparação não encontrado"))?;
-            let direita = Self::parse_expressao(direita_par)?;
-            
-            return Ok(Expressao::Operacao {
-                operador: op,
-                esquerda: Box::new(esquerda),
-                direita: Box::new(direita),
-            });
-        }
-        
-        // Se não houver mais partes, é apenas a expressão
-        Ok(esquerda)
+    /// Chamada com o que estiver antes dos parênteses: uma função pelo nome,
+    /// um método (`objeto.metodo(...)`) ou qualquer outra expressão
+    fn parse_chamada(funcao: Expressao, pair: Pair<Rule>) -> Result<Expressao> {
+        let argumentos = pair.into_inner()
+            .map(Self::parse_expressao)
+            .collect::<Result<Vec<_>>>()?;
+        
+        Ok(match funcao {
+            Expressao::Identificador(nome) => Expressao::Chamada { nome, argumentos },
+            Expressao::AcessoMembro { objeto, membro } => Expressao::ChamadaMetodo {
+                objeto,
+                metodo: membro,
+                argumentos,
+            },
+            funcao => Expressao::ChamadaExpressao {
+                funcao: Box::new(funcao),
+                argumentos,
+            },
+        })
     }
     
-    fn parse_soma(pair: Pair<Rule>) -> Result<Expressao> {
-        let mut inner = pair.into_inner();
-        
-        let primeiro_par = inner.next()
-            .ok_or_else(|| anyhow!("Primeiro termo da soma não encontrado"))?;
-        let mut expr = Self::parse_expressao(primeiro_par)?;
-        
-        // Processar operações de soma/subtração em sequência
-        while let Some(op_par) = inner.next() {
-            let op = match op_par.as_str() {
-                "+" => Operador::Soma,
-                "-" => Operador::Subtracao,
-                _ => return Err(anyhow!("Operador de soma/subtração desconhecido: {}", op_par.as_str())),
-            };
-            
-            let termo_par = inner.next()
-                .ok_or_else(|| anyhow!("Termo após operador não encontrado"))?;
-            let termo = Self::parse_expressao(termo_par)?;
-            
-            expr = Expressao::Operacao {
-                operador: op,
-                esquerda: Box::new(expr),
-                direita: Box::new(termo),
-            };
+    fn parse_indice(alvo: Expressao, pair: Pair<Rule>) -> Result<Expressao> {
+        let conteudo = pair.into_inner().next()
+            .ok_or_else(|| anyhow!("Índice vazio"))?;
+        let alvo = Box::new(alvo);
+        
+        if conteudo.as_rule() != Rule::fatia {
+            let indice = Box::new(Self::parse_expressao(conteudo)?);
+            return Ok(Expressao::Indice { alvo, indice });
         }
         
-        Ok(expr)
-    }
-    
-    fn parse_termo(pair: Pair<Rule>) -> Result<Expressao> {
-        let mut inner = pair.into_inner();
-        
-        let primeiro_par = inner.next()
-            .ok_or_else(|| anyhow!("Primeiro fator do termo não encontrado"))?;
-        let mut expr = Self::parse_expressao(primeiro_par)?;
-        
-        // Processar operações de multiplicação/divisão em sequência
-        while let Some(op_par) = inner.next() {
-            let op = match op_par.as_str() {
-                "*" => Operador::Multiplicacao,
-                "/" => Operador::Divisao,
-                "resto" | "%" => Operador::Resto,
-                _ => return Err(anyhow!("Operador de multiplicação/divisão desconhecido: {}", op_par.as_str())),
-            };
-            
-            let fator_par = inner.next()
-                .ok_or_else(|| anyhow!("Fator após operador não encontrado"))?;
-            let fator = Self::parse_expressao(fator_par)?;
-            
-            expr = Expressao::Operacao {
-                operador: op,
-                esquerda: Box::new(expr),
-                direita: Box::new(fator),
-            };
+        // Os limites ficam antes e depois do `..`
+        let mut inicio = None;
+        let mut fim = None;
+        let mut depois_do_operador = false;
+        for parte in conteudo.into_inner() {
+            match parte.as_rule() {
+                Rule::op_fatia => depois_do_operador = true,
+                _ if depois_do_operador => fim = Some(Box::new(Self::parse_expressao(parte)?)),
+                _ => inicio = Some(Box::new(Self::parse_expressao(parte)?)),
+            }
         }
         
-        Ok(expr)
+        Ok(Expressao::Fatia { alvo, inicio, fim })
     }
     
-    fn parse_fator(pair: Pair<Rule>) -> Result<Expressao> {
-        let inner = pair.clone().into_inner().next()
-            .ok_or_else(|| anyhow!("Fator vazio"))?;
-        
+    fn parse_primario(inner: Pair<Rule>) -> Result<Expressao> {
         match inner.as_rule() {
             Rule::texto_literal => Self::parse_texto_literal(inner),
+            // A gramática só aceita números bem formados; o que ainda pode
+            // falhar é um valor fora dos limites
             Rule::numero_literal => {
-                let valor = inner.as_str().parse::<f64>()
-                    .map_err(|_| anyhow!("Não foi possível converter para número: {}", inner.as_str()))?;
-                Ok(Expressao::NumeroLiteral(valor))
+                match literal_numerico(inner.as_str()) {
+                    Ok(Token::InteiroLiteral(valor)) => Ok(Expressao::InteiroLiteral(valor)),
+                    Ok(Token::NumeroLiteral(valor)) => Ok(Expressao::NumeroLiteral(valor)),
+                    _ => Err(ErroSintatico::no_par(&inner, "um número dentro dos limites").into()),
+                }
             },
             Rule::logico_literal => {
                 let valor = match inner.as_str() {
@@ -812,29 +1521,52 @@ impl PBRParser {
             Rule::nada_literal => Ok(Expressao::Nada),
             Rule::lista_literal => Self::parse_lista_literal(inner),
             Rule::dicionario_literal => Self::parse_dicionario_literal(inner),
-            Rule::chamada => Self::parse_chamada(inner),
-            Rule::acesso_membro => Self::parse_acesso_membro(inner),
-            Rule::identificador => Ok(Expressao::Identificador(inner.as_str().to_string())),
-            Rule::expressao => Self::parse_expressao(inner),
-            _ => {
-                // Verificar se é uma negação
-                if inner.as_str() == "!" || inner.as_str() == "não" {
-                    let next_pair = pair.into_inner().next()
-                        .ok_or_else(|| anyhow!("Expressão após operador de negação não encontrada"))?;
-                    
-                    if let Some(expr_pair) = next_pair.into_inner().next() {
-                        let expressao = Self::parse_expressao(expr_pair)?;
-                        
-                        return Ok(Expressao::Negacao {
-                            expressao: Box::new(expressao),
-                        });
-                    } else {
-                        return Err(anyhow!("Expressão após operador de negação não encontrada"));
-                    }
+            Rule::novo_modelo => {
+                let modelo_par = inner.into_inner().next()
+                    .ok_or_else(|| anyhow!("Nome do modelo não encontrado após 'nova'"))?;
+                Ok(Expressao::NovaInstancia { modelo: nome_identificador(&modelo_par) })
+            },
+            Rule::literal_modelo => {
+                let mut partes = inner.into_inner();
+                let modelo_par = partes.next()
+                    .ok_or_else(|| anyhow!("Nome do modelo não encontrado no literal"))?;
+                let valores = partes.next()
+                    .ok_or_else(|| anyhow!("Campos não encontrados no literal de modelo"))?;
+                
+                let mut campos = Vec::new();
+                for valor_campo in valores.into_inner() {
+                    let mut campo_inner = valor_campo.into_inner();
+                    let (Some(nome_par), Some(valor_par)) = (campo_inner.next(), campo_inner.next()) else {
+                        return Err(anyhow!("Campo incompleto no literal de modelo"));
+                    };
+                    campos.push((nome_identificador(&nome_par), Self::parse_expressao(valor_par)?));
                 }
                 
-                Err(anyhow!("Tipo de fator desconhecido: {:?}", inner.as_rule()))
-            }
+                Ok(Expressao::LiteralModelo { modelo: nome_identificador(&modelo_par), campos })
+            },
+            Rule::eu => Ok(Expressao::Eu),
+            Rule::funcao_anonima => {
+                let mut parametros = Vec::new();
+                let mut tipo_retorno = None;
+                let mut corpo = None;
+                for parte in inner.into_inner() {
+                    match parte.as_rule() {
+                        Rule::parametros => parametros = Self::parse_parametros(parte)?,
+                        Rule::tipo => tipo_retorno = Some(Self::parse_tipo(parte)?),
+                        Rule::bloco => corpo = Some(Self::parse_bloco(parte)?),
+                        _ => return Err(anyhow!("Regra inesperada na função anônima: {:?}", parte.as_rule())),
+                    }
+                }
+                let corpo = corpo.ok_or_else(|| anyhow!("Corpo da função anônima não encontrado"))?;
+                Ok(Expressao::FuncaoAnonima { parametros, tipo_retorno, corpo: Box::new(corpo) })
+            },
+            Rule::identificador => Ok(Expressao::Identificador(nome_identificador(&inner))),
+            Rule::agrupamento => {
+                let expressao_par = inner.into_inner().next()
+                    .ok_or_else(|| anyhow!("Expressão vazia entre parênteses"))?;
+                Self::parse_expressao(expressao_par)
+            },
+            _ => Err(anyhow!("Tipo de fator desconhecido: {:?}", inner.as_rule())),
         }
     }
     
@@ -847,7 +1579,7 @@ impl PBRParser {
                 // Remover as aspas do início e fim
                 let texto = inner.as_str();
                 let sem_aspas = &texto[1..texto.len()-1];
-                Ok(Expressao::TextoLiteral(sem_aspas.to_string()))
+                Ok(Expressao::TextoLiteral(decodificar_escapes(sem_aspas)?))
             },
             Rule::texto_multilinha => {
                 // Remover as três aspas do início e fim
@@ -856,11 +1588,23 @@ impl PBRParser {
                 Ok(Expressao::TextoLiteral(sem_aspas.to_string()))
             },
             Rule::texto_interpolado => {
-                // Interpolação é tratada como concatenação de strings
-                // Implementação simplificada: apenas retorna o texto literal
-                let texto = inner.as_str();
-                let sem_aspas = &texto[1..texto.len()-1];
-                Ok(Expressao::TextoLiteral(sem_aspas.to_string()))
+                let mut partes = Vec::new();
+                
+                for parte in inner.into_inner() {
+                    match parte.as_rule() {
+                        Rule::texto_parte => {
+                            partes.push(ParteTexto::Texto(decodificar_escapes(parte.as_str())?));
+                        },
+                        Rule::interpolacao => {
+                            let expr_par = parte.into_inner().next()
+                                .ok_or_else(|| anyhow!("Expressão vazia na interpolação"))?;
+                            partes.push(ParteTexto::Expressao(Self::parse_expressao(expr_par)?));
+                        },
+                        _ => return Err(anyhow!("Parte de texto desconhecida: {:?}", parte.as_rule())),
+                    }
+                }
+                
+                Ok(Expressao::TextoInterpolado(partes))
             },
             _ => Err(anyhow!("Tipo de texto literal desconhecido: {:?}", inner.as_rule())),
         }
@@ -870,7 +1614,7 @@ impl PBRParser {
         let mut elementos = Vec::new();
         
         for elemento in pair.into_inner() {
-            if elemento.as_rule() == Rule::expressao {
+            if elemento.as_rule() == Rule::expressao_aninhada {
                 let expr = Self::parse_expressao(elemento)?;
                 elementos.push(expr);
             }
@@ -900,59 +1644,214 @@ impl PBRParser {
         
         Ok(Expressao::DicionarioLiteral(pares))
     }
+}
+
+/// Ponto de entrada do parser, usado pela CLI e pela biblioteca. Usa a
+/// gramática pest; o parser baseado em tokens (`analisar`) não é mais usado.
+pub fn analisar_codigo(codigo: &str) -> Result<Programa> {
+    analisar_com_pest(codigo)
+}
+
+/// Analisa o código sem parar no primeiro erro de sintaxe.
+///
+/// Cada declaração com erro vira `Declaracao::Erro` e a análise continua na
+/// próxima fronteira de declaração (depois de `;` ou do `}` que fecha o
+/// bloco, ou antes de uma palavra-chave como `faça` e `modelo`). Devolve o
+/// programa parcial junto com todos os erros encontrados, na ordem do código.
+pub fn analisar_com_recuperacao(codigo: &str) -> (Programa, Vec<ErroSintatico>) {
+    // O código é tokenizado uma só vez, para encontrar o fim das declarações
+    // com erro e os números mal formados
+    let (tokens, erros_lexicos) = tokenizar_com_recuperacao(codigo);
+    let lexico = Lexico { tokens, numeros: numeros_invalidos(&erros_lexicos) };
     
-    fn parse_chamada(pair: Pair<Rule>) -> Result<Expressao> {
-        let mut inner = pair.into_inner();
-        
-        let nome_par = inner.next()
-            .ok_or_else(|| anyhow!("Nome da função não encontrado na chamada"))?;
-        let nome = nome_par.as_str().to_string();
-        
-        let mut argumentos = Vec::new();
-        
-        // Processa todos os argumentos
-        for arg in inner {
-            if arg.as_rule() == Rule::expressao {
-                let expr = Self::parse_expressao(arg)?;
-                argumentos.push(expr);
+    let mut programa = Programa::new();
+    let mut erros = Vec::new();
+    for declaracao in recuperar_declaracoes(codigo, &lexico, 0..codigo.len(), &mut erros) {
+        programa.adicionar_declaracao(declaracao);
+    }
+    
+    (programa, erros)
+}
+
+/// O que o lexer encontrou no código inteiro, usado na recuperação de erros
+struct Lexico<'a> {
+    tokens: Vec<TokenComPosicao<'a>>,
+    numeros: Vec<Range<usize>>,
+}
+
+/// Analisa, uma a uma, as declarações do trecho `intervalo` de `codigo`
+fn recuperar_declaracoes(
+    codigo: &str,
+    lexico: &Lexico,
+    intervalo: Range<usize>,
+    erros: &mut Vec<ErroSintatico>,
+) -> Vec<Declaracao> {
+    let fim = intervalo.end;
+    let mut declaracoes = Vec::new();
+    let mut posicao = pular_trivia(codigo, intervalo.start, fim);
+    
+    while posicao < fim {
+        match PBRParser::parse(Rule::declaracao, &codigo[posicao..fim]) {
+            Ok(mut pares) => {
+                let par = pares.next().expect("declaração analisada sem par");
+                let tamanho = par.as_span().end();
+                
+                match PBRParser::parse_declaracao(par) {
+                    Ok(declaracao) => declaracoes.push(declaracao),
+                    Err(erro) => {
+                        let erro = match erro.downcast::<ErroSintatico>() {
+                            Ok(erro) => erro.deslocado(codigo, posicao),
+                            Err(_) => ErroSintatico::na_posicao(codigo, posicao, Vec::new()),
+                        };
+                        erros.push(erro);
+                        declaracoes.push(Declaracao::Erro);
+                    }
+                }
+                
+                posicao = pular_trivia(codigo, posicao + tamanho, fim);
+            },
+            Err(erro) => {
+                let posicao_erro = posicao + posicao_do_erro(&erro);
+                let (proxima, bloco) = sincronizar(codigo, &lexico.tokens, posicao, posicao_erro, fim);
+                
+                // Um erro dentro do corpo de um bloco é procurado nas
+                // declarações do próprio bloco, que podem ter outros erros
+                let antes = erros.len();
+                if let Some(bloco) = bloco {
+                    recuperar_declaracoes(codigo, lexico, bloco, erros);
+                }
+                if erros.len() == antes {
+                    erros.push(ErroSintatico::de_pest(codigo, posicao, erro, &lexico.numeros));
+                }
+                
+                declaracoes.push(Declaracao::Erro);
+                posicao = pular_trivia(codigo, proxima, fim);
             }
         }
-        
-        Ok(Expressao::Chamada { nome, argumentos })
     }
     
-    fn parse_acesso_membro(pair: Pair<Rule>) -> Result<Expressao> {
-        let mut inner = pair.into_inner();
-        
-        let objeto_par = inner.next()
-            .ok_or_else(|| anyhow!("Objeto não encontrado no acesso a membro"))?;
-        let objeto = Expressao::Identificador(objeto_par.as_str().to_string());
-        
-        let membro_par = inner.next()
-            .ok_or_else(|| anyhow!("Membro não encontrado no acesso a membro"))?;
-        let membro = membro_par.as_str().to_string();
-        
-        Ok(Expressao::AcessoMembro {
-            objeto: Box::new(objeto),
-            membro,
-        })
+    declaracoes
+}
+
+/// Posição do primeiro token a partir de `posicao`, pulando espaços e
+/// comentários
+fn pular_trivia(codigo: &str, posicao: usize, fim: usize) -> usize {
+    let mut lexer = Lexer::new(&codigo[posicao..fim]);
+    match lexer.proximo_token_com_trivia() {
+        Ok((_, token)) if token.token != Token::EOF => posicao + token.inicio,
+        Ok(_) => fim,
+        Err(erro) => posicao + erro.intervalo().start,
     }
 }
 
-// Esta função seria chamada pelo main.rs, escolhendo qual implementação usar
-pub fn analisar_codigo(codigo: &str) -> Result<Programa> {
-    // Utilizamos a implementação baseada em Pest
-    analisar_com_pest(codigo)
+/// Encontra o fim da declaração com erro que começa em `inicio`.
+///
+/// Devolve onde a próxima declaração começa e, quando o erro está dentro do
+/// corpo de um bloco da declaração (`faça`, `se`, `enquanto`...), o
+/// intervalo desse corpo, sem as chaves.
+fn sincronizar(
+    codigo: &str,
+    tokens: &[TokenComPosicao],
+    inicio: usize,
+    erro: usize,
+    fim: usize,
+) -> (usize, Option<Range<usize>>) {
+    let tokens = &tokens[tokens.partition_point(|t| t.inicio < inicio)..tokens.partition_point(|t| t.inicio < fim)];
+    let tem_blocos = tokens.iter()
+        .find(|t| !matches!(t.token, Token::ComentarioDoc(_)) && !eh_palavra(&t.token, &["público", "publico"]))
+        .is_some_and(|t| {
+            matches!(t.token, Token::Faca | Token::Se | Token::ParaCada | Token::QuandoDerErro)
+                || eh_palavra(&t.token, &["enquanto", "repita", "módulo", "modulo", "caixote"])
+        });
+    
+    let mut profundidade = 0;
+    // Parênteses e colchetes abertos, dentro dos quais a quebra de linha
+    // não encerra a declaração
+    let mut agrupamentos = 0;
+    let mut corpo = None;
+    let mut bloco_com_erro = None;
+    let mut continuacao = false;
     
-    // Alternativa baseada em tokens (legado)
-    // let tokens = crate::lexer::tokenizar(codigo)?;
-    // analisar(tokens)
+    for (indice, token) in tokens.iter().enumerate() {
+        let (comeco, termino) = (token.inicio, token.fim);
+        
+        match token.token {
+            Token::EOF => break,
+            Token::AbreChave => {
+                profundidade += 1;
+                if profundidade == 1 {
+                    corpo = Some(termino);
+                }
+            },
+            // `}` sem `{`: pertence a esta declaração com erro
+            Token::FechaChave if profundidade == 0 => return (termino, bloco_com_erro),
+            Token::FechaChave => {
+                profundidade -= 1;
+                if profundidade > 0 {
+                    continue;
+                }
+                
+                let abertura = corpo.take().unwrap_or(comeco);
+                if tem_blocos && abertura <= erro && erro <= comeco {
+                    bloco_com_erro = Some(abertura..comeco);
+                }
+                
+                // `senão`, `até` e `se falhar com` continuam a declaração
+                let proximo = tokens.get(indice + 1).map(|t| &t.token);
+                let depois = tokens.get(indice + 2).map(|t| &t.token);
+                continuacao = matches!(proximo, Some(Token::Senao | Token::Ate))
+                    || (proximo == Some(&Token::Se) && depois == Some(&Token::FalharCom));
+                if !continuacao {
+                    return (termino, bloco_com_erro);
+                }
+            },
+            Token::PontoVirgula if profundidade == 0 => return (termino, bloco_com_erro),
+            Token::AbreParentese | Token::AbreColchete => {
+                agrupamentos += 1;
+                continuacao = false;
+            },
+            Token::FechaParentese | Token::FechaColchete => {
+                agrupamentos -= 1;
+                continuacao = false;
+            },
+            _ if profundidade == 0 && indice > 0 && comeco >= erro && !continuacao
+                && inicia_declaracao(&token.token) => {
+                return (comeco, bloco_com_erro);
+            },
+            // Sem `;`, a declaração com erro termina na quebra de linha
+            _ if profundidade == 0 && agrupamentos <= 0 && indice > 0 && comeco >= erro
+                && !continuacao && token.token != Token::Ponto
+                && codigo[tokens[indice - 1].fim..comeco].contains('\n') => {
+                return (comeco, bloco_com_erro);
+            },
+            _ => continuacao = false,
+        }
+    }
+    
+    (fim, bloco_com_erro)
+}
+
+/// Tokens que só aparecem no início de uma declaração
+fn inicia_declaracao(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Faca | Token::Mostre | Token::Volte | Token::Se | Token::ParaCada | Token::Modelo
+            | Token::QuandoDerErro | Token::Pense
+    ) || eh_palavra(token, &[
+        "enquanto", "repita", "escolha", "pare", "continue", "módulo", "modulo", "caixote",
+        "use", "importar", "público", "publico",
+    ])
+}
+
+/// Palavras-chave da gramática que o lexer trata como identificadores
+fn eh_palavra(token: &Token, palavras: &[&str]) -> bool {
+    matches!(token, Token::Identificador(nome) if palavras.contains(&nome.as_ref()))
 }
 
 // Analisa uma expressão que pode incluir strings, identificadores, números e operações
-fn analisar_expressao(i: &mut usize, tokens: &[Token]) -> Result<Expressao> {
+fn analisar_expressao(i: &mut usize, tokens: &[Token], posicoes: &[(usize, usize)]) -> Result<Expressao> {
     // Primeiro termo (lado esquerdo)
-    let mut expr = analisar_termo(*i, tokens)?;
+    let mut expr = analisar_termo(*i, tokens, posicoes)?;
     *i += 1; // Avançar após o primeiro termo
     
     // Verifica se há operações (como +)
@@ -961,7 +1860,7 @@ fn analisar_expressao(i: &mut usize, tokens: &[Token]) -> Result<Expressao> {
             *i += 1; // Avançar após o operador
             
             if *i < tokens.len() {
-                let termo_direito = analisar_termo(*i, tokens)?;
+                let termo_direito = analisar_termo(*i, tokens, posicoes)?;
                 *i += 1; // Avançar após o termo
                 
                 expr = Expressao::Operacao {
@@ -970,7 +1869,7 @@ fn analisar_expressao(i: &mut usize, tokens: &[Token]) -> Result<Expressao> {
                     direita: Box::new(termo_direito),
                 };
             } else {
-                return Err(anyhow::anyhow!("Expressão incompleta após '+'"));
+                return Err(ErroSintatico::em_token(tokens, posicoes, *i, &["uma expressão"]));
             }
         } else {
             // Não é uma operação, sair do loop
@@ -981,21 +1880,69 @@ fn analisar_expressao(i: &mut usize, tokens: &[Token]) -> Result<Expressao> {
     Ok(expr)
 }
 
+fn eh_atribuicao(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Igual
+            | Token::MaisIgual
+            | Token::MenosIgual
+            | Token::VezesIgual
+            | Token::DivididoIgual
+            | Token::RestoIgual
+    )
+}
+
+// Analisa o operador e o valor de uma atribuição. Atribuições compostas são
+// convertidas para a forma simples: `x += 1` vira `x = x + 1`.
+fn analisar_atribuicao(nome: String, i: &mut usize, tokens: &[Token], posicoes: &[(usize, usize)]) -> Result<Expressao> {
+    let operador = match tokens.get(*i) {
+        Some(Token::Igual) => None,
+        Some(Token::MaisIgual) => Some(Operador::Soma),
+        Some(Token::MenosIgual) => Some(Operador::Subtracao),
+        Some(Token::VezesIgual) => Some(Operador::Multiplicacao),
+        Some(Token::DivididoIgual) => Some(Operador::Divisao),
+        Some(Token::RestoIgual) => Some(Operador::Resto),
+        _ => return Err(ErroSintatico::em_token(tokens, posicoes, *i, &["`=`"])),
+    };
+    *i += 1; // Avançar depois do operador
+    
+    if *i >= tokens.len() {
+        return Err(ErroSintatico::em_token(tokens, posicoes, *i, &["uma expressão"]));
+    }
+    
+    let mut valor = analisar_expressao(i, tokens, posicoes)?;
+    if let Some(operador) = operador {
+        valor = Expressao::Operacao {
+            operador,
+            esquerda: Box::new(Expressao::Identificador(nome.clone())),
+            direita: Box::new(valor),
+        };
+    }
+    
+    Ok(Expressao::Atribuicao {
+        nome,
+        valor: Box::new(valor),
+    })
+}
+
 // Analisa um termo simples (literal, identificador, etc.)
-fn analisar_termo(i: usize, tokens: &[Token]) -> Result<Expressao> {
+fn analisar_termo(i: usize, tokens: &[Token], posicoes: &[(usize, usize)]) -> Result<Expressao> {
     if i >= tokens.len() {
-        return Err(anyhow::anyhow!("Esperava um termo"));
+        return Err(ErroSintatico::em_token(tokens, posicoes, i, &["uma expressão"]));
     }
     
     match &tokens[i] {
         Token::TextoLiteral(texto) => {
-            Ok(Expressao::TextoLiteral(texto.clone()))
+            Ok(Expressao::TextoLiteral(texto.to_string()))
+        },
+        Token::InteiroLiteral(valor) => {
+            Ok(Expressao::InteiroLiteral(*valor))
         },
         Token::NumeroLiteral(valor) => {
             Ok(Expressao::NumeroLiteral(*valor))
         },
         Token::Identificador(nome) => {
-            Ok(Expressao::Identificador(nome.clone()))
+            Ok(Expressao::Identificador(nome.to_string()))
         },
         Token::Verdadeiro => {
             Ok(Expressao::LogicoLiteral(true))
@@ -1004,7 +1951,7 @@ fn analisar_termo(i: usize, tokens: &[Token]) -> Result<Expressao> {
             Ok(Expressao::LogicoLiteral(false))
         },
         _ => {
-            Err(anyhow::anyhow!("Termo inválido na expressão"))
+            Err(ErroSintatico::em_token(tokens, posicoes, i, &["uma expressão"]))
         }
     }
 }
@@ -1,74 +1,339 @@
 use anyhow::{anyhow, Result};
-use crate::ast::{Declaracao, Expressao, Operador, Programa, Tipo};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+use crate::ast::{Assinatura, Declaracao, Expressao, Operador, Padrao, ParteTexto, Programa, Tipo};
 
-pub fn gerar_codigo_rust(programa: Programa) -> Result<String> {
+/// Trait usada pelos textos interpolados para mostrar valores como em
+/// PBRLang: lógicos viram `verdadeiro`/`falso`, `nada` aparece por extenso e
+/// números não ganham casas decimais desnecessárias. Cada modelo recebe uma
+/// implementação própria (ver `gerar_exibir_modelo`).
+const PREAMBULO_EXIBIR: &str = r#"trait Exibir {
+    fn exibir(&self) -> String;
+}
+
+impl Exibir for String {
+    fn exibir(&self) -> String { self.clone() }
+}
+
+impl Exibir for &str {
+    fn exibir(&self) -> String { self.to_string() }
+}
+
+impl Exibir for i64 {
+    fn exibir(&self) -> String { self.to_string() }
+}
+
+impl Exibir for f64 {
+    fn exibir(&self) -> String { self.to_string() }
+}
+
+impl Exibir for bool {
+    fn exibir(&self) -> String { if *self { "verdadeiro".to_string() } else { "falso".to_string() } }
+}
+
+impl Exibir for () {
+    fn exibir(&self) -> String { "nada".to_string() }
+}
+
+impl Exibir for Box<dyn std::error::Error> {
+    fn exibir(&self) -> String { self.to_string() }
+}
+
+impl<T: Exibir> Exibir for Option<T> {
+    fn exibir(&self) -> String {
+        match self {
+            Some(valor) => valor.exibir(),
+            None => "nada".to_string(),
+        }
+    }
+}
+
+impl<T: Exibir> Exibir for Vec<T> {
+    fn exibir(&self) -> String {
+        let itens: Vec<String> = self.iter().map(|item| item.exibir()).collect();
+        format!("[{}]", itens.join(", "))
+    }
+}
+
+impl<K: Exibir, V: Exibir> Exibir for HashMap<K, V> {
+    fn exibir(&self) -> String {
+        let pares: Vec<String> = self.iter().map(|(chave, valor)| format!("{}: {}", chave.exibir(), valor.exibir())).collect();
+        format!("{{{}}}", pares.join(", "))
+    }
+}
+
+"#;
+
+/// Erros de execução PBRLang. `falhar` interrompe o programa com uma
+/// mensagem em português (sem o texto de pânico do Rust), a menos que o erro
+/// aconteça dentro de um `quando der erro`, que o captura com `capturar`.
+const PREAMBULO_ERROS: &str = r#"#[derive(Debug)]
+struct ErroPbr(String);
+
+impl std::fmt::Display for ErroPbr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { f.write_str(&self.0) }
+}
+
+impl std::error::Error for ErroPbr {}
+
+thread_local! {
+    // Quantos `quando der erro` estão ativos
+    static CAPTURAS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+fn falhar(mensagem: String) -> ! {
+    static GANCHO: std::sync::Once = std::sync::Once::new();
+    GANCHO.call_once(|| {
+        let padrao = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| match info.payload().downcast_ref::<ErroPbr>() {
+            Some(_) if CAPTURAS.with(|c| c.get()) > 0 => {},
+            Some(erro) => eprintln!("Erro: {}", erro),
+            None => padrao(info),
+        }));
+    });
+    std::panic::panic_any(ErroPbr(mensagem))
+}
+
+fn capturar(bloco: impl FnOnce() -> Result<(), Box<dyn std::error::Error>>) -> Result<(), Box<dyn std::error::Error>> {
+    CAPTURAS.with(|c| c.set(c.get() + 1));
+    let resultado = std::panic::catch_unwind(std::panic::AssertUnwindSafe(bloco));
+    CAPTURAS.with(|c| c.set(c.get() - 1));
+    match resultado {
+        Ok(resultado) => resultado,
+        Err(panico) => match panico.downcast::<ErroPbr>() {
+            Ok(erro) => Err(erro),
+            Err(panico) => std::panic::resume_unwind(panico),
+        },
+    }
+}
+
+"#;
+
+/// Índices e fatias com verificação de limites: um índice inválido vira um
+/// erro PBRLang (`falhar`) em vez de um pânico do Rust
+const PREAMBULO_INDICES: &str = r#"trait Indexar<I> {
+    type Saida;
+    fn indexar(&self, indice: I) -> Self::Saida;
+}
+
+impl<T: Clone> Indexar<i64> for Vec<T> {
+    type Saida = T;
+    fn indexar(&self, indice: i64) -> T {
+        let valor = if indice >= 0 { self.get(indice as usize) } else { None };
+        match valor {
+            Some(valor) => valor.clone(),
+            None => falhar(format!("Índice {} fora dos limites da lista (tamanho {})", indice, self.len())),
+        }
+    }
+}
+
+impl Indexar<i64> for str {
+    type Saida = String;
+    fn indexar(&self, indice: i64) -> String {
+        let valor = if indice >= 0 { self.chars().nth(indice as usize) } else { None };
+        match valor {
+            Some(c) => c.to_string(),
+            None => falhar(format!("Índice {} fora dos limites do texto (tamanho {})", indice, self.chars().count())),
+        }
+    }
+}
+
+impl<K: std::hash::Hash + Eq + Exibir, V: Clone> Indexar<K> for HashMap<K, V> {
+    type Saida = V;
+    fn indexar(&self, chave: K) -> V {
+        match self.get(&chave) {
+            Some(valor) => valor.clone(),
+            None => falhar(format!("Chave {} não encontrada no dicionário", chave.exibir())),
+        }
+    }
+}
+
+trait Fatiar {
+    type Saida;
+    fn tamanho(&self) -> usize;
+    fn fatia(&self, inicio: usize, fim: usize) -> Self::Saida;
+    
+    fn fatiar(&self, inicio: Option<i64>, fim: Option<i64>) -> Self::Saida {
+        let tamanho = self.tamanho();
+        let (a, b) = (inicio.unwrap_or(0), fim.unwrap_or(tamanho as i64));
+        if a < 0 || a > b || b > tamanho as i64 {
+            falhar(format!("Fatia {}..{} fora dos limites (tamanho {})", a, b, tamanho));
+        }
+        self.fatia(a as usize, b as usize)
+    }
+}
+
+impl<T: Clone> Fatiar for Vec<T> {
+    type Saida = Vec<T>;
+    fn tamanho(&self) -> usize { self.len() }
+    fn fatia(&self, inicio: usize, fim: usize) -> Vec<T> { self[inicio..fim].to_vec() }
+}
+
+impl Fatiar for str {
+    type Saida = String;
+    fn tamanho(&self) -> usize { self.chars().count() }
+    fn fatia(&self, inicio: usize, fim: usize) -> String { self.chars().skip(inicio).take(fim - inicio).collect() }
+}
+
+"#;
+
+/// Iteração: `para cada ... em` percorre uma cópia dos itens da coleção
+/// (os caracteres de um texto, os pares de um dicionário), e `para cada
+/// ... passo` usa `intervalo`, que também conta para baixo
+const PREAMBULO_ITERACAO: &str = r#"trait Iteravel {
+    type Item;
+    fn itens(&self) -> Vec<Self::Item>;
+}
+
+impl<T: Clone> Iteravel for Vec<T> {
+    type Item = T;
+    fn itens(&self) -> Vec<T> { self.clone() }
+}
+
+impl Iteravel for str {
+    type Item = String;
+    fn itens(&self) -> Vec<String> { self.chars().map(|c| c.to_string()).collect() }
+}
+
+impl<K: Clone + std::hash::Hash + Eq, V: Clone> Iteravel for HashMap<K, V> {
+    type Item = (K, V);
+    fn itens(&self) -> Vec<(K, V)> { self.iter().map(|(k, v)| (k.clone(), v.clone())).collect() }
+}
+
+fn intervalo(inicio: i64, fim: i64, passo: i64) -> impl Iterator<Item = i64> {
+    if passo == 0 {
+        falhar("O passo de um intervalo não pode ser zero".to_string());
+    }
+    std::iter::successors(Some(inicio), move |atual| atual.checked_add(passo))
+        .take_while(move |atual| if passo > 0 { *atual <= fim } else { *atual >= fim })
+}
+
+"#;
+
+/// Literais de modelo: `converter` ajusta o valor ao tipo do campo
+/// (`"Ana"` para `String`, `30` para `f64`)
+const PREAMBULO_MODELOS: &str = r#"trait Converter<T> {
+    fn converter(self) -> T;
+}
+
+impl<T> Converter<T> for T {
+    fn converter(self) -> T { self }
+}
+
+impl Converter<String> for &str {
+    fn converter(self) -> String { self.to_string() }
+}
+
+impl Converter<f64> for i64 {
+    fn converter(self) -> f64 { self as f64 }
+}
+
+"#;
+
+pub fn gerar_codigo_rust(mut programa: Programa) -> Result<String> {
     let mut saida = String::new();
     
+    // Os padrões de `escolha` dependem das enumerações declaradas no programa
+    let mut enumeracoes = Enumeracoes::default();
+    for declaracao in &mut programa.declaracoes {
+        enumeracoes.coletar(declaracao)?;
+    }
+    for declaracao in &mut programa.declaracoes {
+        enumeracoes.resolver_escolhas(declaracao)?;
+    }
+    let contexto = Contexto::novo(&mut programa.declaracoes)?;
+    
     // Adiciona o preâmbulo padrão
     saida.push_str("use std::io::{self, Write};\n");
     saida.push_str("use std::collections::HashMap;\n\n");
+    saida.push_str(PREAMBULO_EXIBIR);
+    saida.push_str(PREAMBULO_ERROS);
+    saida.push_str(PREAMBULO_INDICES);
+    saida.push_str(PREAMBULO_ITERACAO);
+    saida.push_str(PREAMBULO_MODELOS);
     
     // Gera código para cada declaração
     for declaracao in programa.declaracoes {
-        gerar_declaracao(&declaracao, &mut saida)?;
+        gerar_declaracao(&declaracao, &contexto, &mut saida)?;
         saida.push('\n');
     }
     
     Ok(saida)
 }
-fn gerar_declaracao(decl: &Declaracao, saida: &mut String) -> Result<()> {
+fn gerar_declaracao(decl: &Declaracao, contexto: &Contexto, saida: &mut String) -> Result<()> {
     match decl {
         Declaracao::Variavel { nome, tipo, valor, publico } => {
             if *publico {
                 write!(saida, "pub ")?;
             }
-            write!(saida, "let mut {} = ", nome)?;
+            write!(saida, "let mut {}", identificador_rust(nome))?;
+            if let Some(tipo) = tipo.as_ref().filter(|tipo| contexto.anotavel(tipo)) {
+                write!(saida, ": ")?;
+                gerar_tipo(tipo, saida)?;
+            }
+            write!(saida, " = ")?;
             if let Some(v) = valor {
-                gerar_expressao(v, saida)?;
+                gerar_valor(v, tipo.as_ref(), contexto, saida)?;
             } else {
                 match tipo {
                     Some(Tipo::Texto) => write!(saida, "String::new()")?,
                     Some(Tipo::Numero) => write!(saida, "0.0")?,
+                    Some(Tipo::Inteiro) => write!(saida, "0i64")?,
                     Some(Tipo::Logico) => write!(saida, "false")?,
-                    Some(Tipo::Personalizado(t)) => write!(saida, "{}::default()", t)?,
+                    Some(Tipo::Personalizado(t)) => write!(saida, "{}::default()", identificador_rust(t))?,
+                    Some(Tipo::Opcional(_)) => write!(saida, "None")?,
+                    Some(composto @ (Tipo::Lista(_) | Tipo::Mapa(..) | Tipo::Generico(..))) => {
+                        write!(saida, "<")?;
+                        gerar_tipo(composto, saida)?;
+                        write!(saida, ">::default()")?;
+                    },
+                    Some(Tipo::Void) => write!(saida, "()")?,
+                    Some(Tipo::Funcao(..)) => {
+                        return Err(anyhow!("A variável `{}` guarda uma função e precisa de um valor", nome));
+                    },
                     None => write!(saida, "Default::default()")?,
                 }
             }
-            write!(saida, ";\n")?;
+            writeln!(saida, ";")?;
+            // Declarada depois do valor, que ainda vê uma variável de fora
+            // com o mesmo nome
+            contexto.declarar(nome, tipo.clone().or_else(|| valor.as_ref().and_then(|v| contexto.tipo_de(v))));
         },
         
-        Declaracao::Funcao { nome, parametros, tipo_retorno, corpo, publico } => {
+        Declaracao::Funcao { nome, genericos, parametros, tipo_retorno, corpo, publico, documentacao } => {
+            gerar_documentacao(documentacao, saida)?;
             if *publico {
                 write!(saida, "pub ")?;
             }
-            write!(saida, "fn {}(", nome)?;
-            
-            // Parâmetros
-            for (i, (nome_param, tipo_param)) in parametros.iter().enumerate() {
-                if i > 0 {
-                    write!(saida, ", ")?;
-                }
-                write!(saida, "{}: ", nome_param)?;
-                gerar_tipo(tipo_param, saida)?;
-            }
-            write!(saida, ")")?;
-            
-            // Tipo de retorno
-            if let Some(tipo) = tipo_retorno {
-                write!(saida, " -> ")?;
-                gerar_tipo(tipo, saida)?;
-            }
-            
-            write!(saida, " {{\n")?;
-            gerar_declaracao(corpo, saida)?;
-            write!(saida, "}}\n")?;
+            gerar_assinatura(nome, genericos, None, parametros, tipo_retorno, contexto, saida)?;
+            writeln!(saida, " {{")?;
+            contexto.abrir_funcao(parametros, tipo_retorno);
+            gerar_declaracao(corpo, contexto, saida)?;
+            contexto.fechar();
+            writeln!(saida, "}}")?;
         },
         
-        Declaracao::Modelo { nome, campos, publico } => {
+        // Os métodos de um contrato vão para o `impl` do contrato, junto com
+        // o modelo
+        Declaracao::Metodo { .. } if contexto.contratos.contrato_de(decl).is_some() => {},
+        Declaracao::Metodo { modelo, .. } => {
+            let genericos = contexto.modelos.get(modelo).map_or(&[][..], |declarado| &declarado.genericos);
+            writeln!(saida, "impl{} {}{} {{", parametros_tipo(genericos), identificador_rust(modelo), argumentos_tipo(genericos))?;
+            gerar_metodo(decl, contexto, saida)?;
+            writeln!(saida, "}}")?;
+        },
+        
+        Declaracao::Modelo { nome, genericos, campos, metodos, contratos: cumpridos, publico, documentacao } => {
+            gerar_documentacao(documentacao, saida)?;
+            // Os parâmetros de tipo só são restringidos nos `impl`
+            let tipo = format!("{}{}", identificador_rust(nome), argumentos_tipo(genericos));
+            let parametros = parametros_tipo(genericos);
             if *publico {
-                write!(saida, "#[derive(Default)]\npub struct {} {{\n", nome)?;
+                writeln!(saida, "#[derive(Clone, Debug, Default, PartialEq)]\npub struct {} {{", tipo)?;
             } else {
-                write!(saida, "#[derive(Default)]\nstruct {} {{\n", nome)?;
+                writeln!(saida, "#[derive(Clone, Debug, Default, PartialEq)]\nstruct {} {{", tipo)?;
             }
             
             for (nome_campo, tipo_campo, campo_publico) in campos {
@@ -77,22 +342,82 @@ fn gerar_declaracao(decl: &Declaracao, saida: &mut String) -> Result<()> {
                 } else {
                     write!(saida, "    ")?;
                 }
-                write!(saida, "{}: ", nome_campo)?;
+                write!(saida, "{}: ", identificador_rust(nome_campo))?;
                 gerar_tipo(tipo_campo, saida)?;
-                write!(saida, ",\n")?;
+                writeln!(saida, ",")?;
             }
             
-            write!(saida, "}}\n")?;
+            writeln!(saida, "}}")?;
+            gerar_exibir_modelo(nome, genericos, campos, saida)?;
+            
+            let proprios: Vec<&Declaracao> = metodos.iter()
+                .filter(|metodo| contexto.contratos.contrato_de(metodo).is_none())
+                .collect();
+            if !proprios.is_empty() {
+                writeln!(saida, "impl{} {} {{", parametros, tipo)?;
+                for metodo in proprios {
+                    gerar_metodo(metodo, contexto, saida)?;
+                }
+                writeln!(saida, "}}")?;
+            }
+            
+            for contrato in cumpridos {
+                writeln!(saida, "impl{} {} for {} {{", parametros, identificador_rust(contrato), tipo)?;
+                for metodo in contexto.contratos.implementacao(nome, contrato) {
+                    gerar_metodo(metodo, contexto, saida)?;
+                }
+                writeln!(saida, "}}")?;
+            }
+        },
+        
+        Declaracao::Contrato { nome, metodos, publico, documentacao } => {
+            gerar_documentacao(documentacao, saida)?;
+            if *publico {
+                write!(saida, "pub ")?;
+            }
+            writeln!(saida, "trait {} {{", identificador_rust(nome))?;
+            for assinatura in metodos {
+                gerar_documentacao(&assinatura.documentacao, saida)?;
+                let receptor = contexto.receptor(nome, &assinatura.nome);
+                gerar_assinatura(&assinatura.nome, &[], Some(receptor), &assinatura.parametros, &assinatura.tipo_retorno, contexto, saida)?;
+                writeln!(saida, ";")?;
+            }
+            writeln!(saida, "}}")?;
+        },
+        
+        Declaracao::Enumeracao { nome, variantes, publico, documentacao } => {
+            gerar_documentacao(documentacao, saida)?;
+            writeln!(saida, "#[derive(Clone, Debug, PartialEq)]")?;
+            if *publico {
+                write!(saida, "pub ")?;
+            }
+            writeln!(saida, "enum {} {{", identificador_rust(nome))?;
+            for (variante, campos) in variantes {
+                write!(saida, "    {}", identificador_rust(variante))?;
+                if !campos.is_empty() {
+                    write!(saida, "(")?;
+                    for (i, (_, tipo)) in campos.iter().enumerate() {
+                        if i > 0 { write!(saida, ", ")? }
+                        gerar_tipo(tipo, saida)?;
+                    }
+                    write!(saida, ")")?;
+                }
+                writeln!(saida, ",")?;
+            }
+            writeln!(saida, "}}")?;
+            // As variantes são usadas sem o nome do tipo: `Circulo(2.0)`
+            writeln!(saida, "use self::{}::*;", identificador_rust(nome))?;
+            gerar_exibir_enumeracao(nome, variantes, saida)?;
         },
         
         Declaracao::Modulo { nome, declaracoes } => {
-            write!(saida, "mod {} {{\n", nome)?;
+            writeln!(saida, "mod {} {{", identificador_rust(nome))?;
             
             for decl in declaracoes {
-                gerar_declaracao(decl, saida)?;
+                gerar_declaracao(decl, contexto, saida)?;
             }
             
-            write!(saida, "}}\n")?;
+            writeln!(saida, "}}")?;
         },
         
         Declaracao::Importar { caminho } => {
@@ -101,67 +426,169 @@ fn gerar_declaracao(decl: &Declaracao, saida: &mut String) -> Result<()> {
                 if i > 0 {
                     write!(saida, "::")?;
                 }
-                write!(saida, "{}", parte)?;
+                write!(saida, "{}", identificador_rust(parte))?;
             }
-            write!(saida, ";\n")?;
+            writeln!(saida, ";")?;
         },
         
         Declaracao::Enquanto { condicao, corpo } => {
             write!(saida, "while ")?;
-            gerar_expressao(condicao, saida)?;
-            write!(saida, " {{\n")?;
-            gerar_declaracao(corpo, saida)?;
-            write!(saida, "}}\n")?;
+            gerar_expressao(condicao, contexto, saida)?;
+            writeln!(saida, " {{")?;
+            gerar_declaracao(corpo, contexto, saida)?;
+            writeln!(saida, "}}")?;
         },
         
         Declaracao::Repita { corpo, condicao } => {
-            write!(saida, "loop {{\n")?;
-            gerar_declaracao(corpo, saida)?;
+            writeln!(saida, "loop {{")?;
+            gerar_declaracao(corpo, contexto, saida)?;
             write!(saida, "    if ")?;
-            gerar_expressao(condicao, saida)?;
-            write!(saida, " {{ break; }}\n}}\n")?;
+            gerar_expressao(condicao, contexto, saida)?;
+            writeln!(saida, " {{ break; }}\n}}")?;
         },
         
         Declaracao::Pare => {
-            write!(saida, "break;\n")?;
+            writeln!(saida, "break;")?;
         },
         
         Declaracao::Continue => {
-            write!(saida, "continue;\n")?;
+            writeln!(saida, "continue;")?;
         },
         
         Declaracao::Se { condicao, bloco_se, bloco_senao } => {
             write!(saida, "if ")?;
-            gerar_expressao(condicao, saida)?;
-            write!(saida, " {{\n")?;
-            gerar_declaracao(bloco_se, saida)?;
+            gerar_expressao(condicao, contexto, saida)?;
+            writeln!(saida, " {{")?;
+            gerar_declaracao(bloco_se, contexto, saida)?;
             
-            if let Some(senao) = bloco_senao {
-                write!(saida, "}} else {{\n")?;
-                gerar_declaracao(senao, saida)?;
+            match bloco_senao.as_deref() {
+                // `senão se` vira `else if`, sem aninhar blocos
+                Some(senao @ Declaracao::Se { .. }) => {
+                    write!(saida, "}} else ")?;
+                    return gerar_declaracao(senao, contexto, saida);
+                },
+                Some(senao) => {
+                    writeln!(saida, "}} else {{")?;
+                    gerar_declaracao(senao, contexto, saida)?;
+                },
+                None => {},
             }
             
-            write!(saida, "}}\n")?;
+            writeln!(saida, "}}")?;
         },
         
-        Declaracao::ParaCada { variavel, inicio, fim, corpo } => {
-            write!(saida, "for {} in ", variavel)?;
-            gerar_expressao(inicio, saida)?;
-            write!(saida, "..=")?;
-            gerar_expressao(fim, saida)?;
-            write!(saida, " {{\n")?;
-            gerar_declaracao(corpo, saida)?;
-            write!(saida, "}}\n")?;
+        Declaracao::Escolha { valor, casos, padrao } => {
+            write!(saida, "match &")?;
+            gerar_expressao(valor, contexto, saida)?;
+            writeln!(saida, " {{")?;
+            for (valores, bloco) in casos {
+                gerar_caso(valores, contexto, saida)?;
+                writeln!(saida, " => {{")?;
+                // Os campos são ligados por referência; as cópias deixam o
+                // bloco usá-los como valores
+                let mut ligados: Vec<(&String, Option<Tipo>)> = Vec::new();
+                for valor in valores {
+                    if let Padrao::Variante { nome, campos } = valor {
+                        let tipos = contexto.variantes.get(nome).map_or(&[][..], |(_, tipos)| tipos);
+                        for (i, campo) in campos.iter().enumerate().filter(|(_, c)| *c != "_") {
+                            ligados.push((campo, tipos.get(i).cloned()));
+                        }
+                    }
+                }
+                ligados.dedup_by(|a, b| a.0 == b.0);
+                contexto.abrir(Quadro::default());
+                for (campo, tipo) in ligados {
+                    writeln!(saida, "let {0} = {0}.clone();", identificador_rust(campo))?;
+                    contexto.declarar(campo, tipo);
+                }
+                gerar_declaracao(bloco, contexto, saida)?;
+                contexto.fechar();
+                writeln!(saida, "}},")?;
+            }
+            // Sem `padrão`, uma escolha sobre variantes já foi verificada
+            // como completa (ver `Enumeracoes::resolver_escolhas`)
+            let completa = padrao.is_none() && !casos.is_empty() && casos.iter()
+                .all(|(valores, _)| valores.iter().all(|v| matches!(v, Padrao::Variante { .. })));
+            if !completa {
+                writeln!(saida, "_ => {{")?;
+                if let Some(padrao) = padrao {
+                    gerar_declaracao(padrao, contexto, saida)?;
+                }
+                writeln!(saida, "}},")?;
+            }
+            writeln!(saida, "}}")?;
+        },
+        
+        Declaracao::ParaCada { variavel, inicio, fim, passo, corpo } => {
+            write!(saida, "for {} in ", identificador_rust(variavel))?;
+            match passo {
+                // O passo pode ser negativo, o que `step_by` não aceita
+                Some(passo) => {
+                    write!(saida, "intervalo(")?;
+                    gerar_expressao(inicio, contexto, saida)?;
+                    write!(saida, ", ")?;
+                    gerar_expressao(fim, contexto, saida)?;
+                    write!(saida, ", ")?;
+                    gerar_expressao(passo, contexto, saida)?;
+                    write!(saida, ")")?;
+                },
+                None => {
+                    gerar_expressao(inicio, contexto, saida)?;
+                    write!(saida, "..=")?;
+                    gerar_expressao(fim, contexto, saida)?;
+                },
+            }
+            writeln!(saida, " {{")?;
+            contexto.abrir(Quadro::default());
+            contexto.declarar(variavel, Some(Tipo::Inteiro));
+            gerar_declaracao(corpo, contexto, saida)?;
+            contexto.fechar();
+            writeln!(saida, "}}")?;
+        },
+        
+        Declaracao::ParaCadaEm { variavel, colecao, corpo } => {
+            write!(saida, "for {} in (", identificador_rust(variavel))?;
+            gerar_expressao(colecao, contexto, saida)?;
+            writeln!(saida, ").itens() {{")?;
+            let item = match contexto.tipo_de(colecao) {
+                Some(Tipo::Lista(item)) => Some(*item),
+                Some(Tipo::Texto) => Some(Tipo::Texto),
+                _ => None,
+            };
+            contexto.abrir(Quadro::default());
+            contexto.declarar(variavel, item);
+            gerar_declaracao(corpo, contexto, saida)?;
+            contexto.fechar();
+            writeln!(saida, "}}")?;
+        },
+        
+        Declaracao::ParaCadaPar { chave, valor, colecao, corpo } => {
+            write!(saida, "for ({}, {}) in (", identificador_rust(chave), identificador_rust(valor))?;
+            gerar_expressao(colecao, contexto, saida)?;
+            writeln!(saida, ").itens() {{")?;
+            let (tipo_chave, tipo_valor) = match contexto.tipo_de(colecao) {
+                Some(Tipo::Mapa(chave, valor)) => (Some(*chave), Some(*valor)),
+                _ => (None, None),
+            };
+            contexto.abrir(Quadro::default());
+            contexto.declarar(chave, tipo_chave);
+            contexto.declarar(valor, tipo_valor);
+            gerar_declaracao(corpo, contexto, saida)?;
+            contexto.fechar();
+            writeln!(saida, "}}")?;
         },
         
         Declaracao::QuandoDerErro { bloco_try, variavel_erro, bloco_catch } => {
-            write!(saida, "match (|| -> Result<(), Box<dyn std::error::Error>> {{\n")?;
-            gerar_declaracao(bloco_try, saida)?;
-            write!(saida, "    Ok(())\n}}() {{\n")?;
-            write!(saida, "    Ok(_) => {{}},\n")?;
-            write!(saida, "    Err({}) => {{\n", variavel_erro)?;
-            gerar_declaracao(bloco_catch, saida)?;
-            write!(saida, "    }}\n}}\n")?;
+            writeln!(saida, "match capturar(|| {{")?;
+            gerar_declaracao(bloco_try, contexto, saida)?;
+            writeln!(saida, "    Ok(())\n}}) {{")?;
+            writeln!(saida, "    Ok(_) => {{}},")?;
+            writeln!(saida, "    Err({}) => {{", identificador_rust(variavel_erro))?;
+            contexto.abrir(Quadro::default());
+            contexto.declarar(variavel_erro, None);
+            gerar_declaracao(bloco_catch, contexto, saida)?;
+            contexto.fechar();
+            writeln!(saida, "    }}\n}}")?;
         },
         
         Declaracao::Retorno(expr) => {
@@ -169,44 +596,74 @@ fn gerar_declaracao(decl: &Declaracao, saida: &mut String) -> Result<()> {
             
             if let Some(e) = expr {
                 write!(saida, " ")?;
-                gerar_expressao(e, saida)?;
+                gerar_valor(e, contexto.retorno().as_ref(), contexto, saida)?;
             }
             
-            write!(saida, ";\n")?;
+            writeln!(saida, ";")?;
         },
         
         Declaracao::Mostrar(expr) => {
-            write!(saida, "println!(\"{:?}\", ")?;
-            gerar_expressao(expr, saida)?;
-            write!(saida, ");\n")?;
+            // Como na interpolação: `verdadeiro`, `nada` e textos sem aspas
+            write!(saida, "println!(\"{{}}\", ")?;
+            gerar_exibicao(expr, contexto, saida)?;
+            writeln!(saida, ");")?;
         },
         
         Declaracao::Bloco(declaracoes) => {
+            contexto.abrir(Quadro::default());
             for decl in declaracoes {
-                gerar_declaracao(decl, saida)?;
+                gerar_declaracao(decl, contexto, saida)?;
             }
+            contexto.fechar();
         },
         
         Declaracao::Expressao(expr) => {
-            gerar_expressao(expr, saida)?;
-            write!(saida, ";\n")?;
+            gerar_expressao(expr, contexto, saida)?;
+            writeln!(saida, ";")?;
+        },
+        
+        Declaracao::Erro => {
+            return Err(anyhow!("O programa tem erros de sintaxe e não pode ser convertido"));
         },
-        _ => return Err(anyhow!("Tipo de declaração não suportado: {:?}", decl)),
     }
     
     Ok(())
 }
-fn gerar_expressao(expr: &Expressao, saida: &mut String) -> Result<()> {
+fn gerar_expressao(expr: &Expressao, contexto: &Contexto, saida: &mut String) -> Result<()> {
     match expr {
         Expressao::TextoLiteral(texto) => write!(saida, "\"{}\"", escapar_string_para_rust(texto))?,
-        Expressao::NumeroLiteral(num) => write!(saida, "{}", num)?,
+        Expressao::TextoInterpolado(partes) => {
+            // `format!` com um `{}` para cada expressão; as chaves do texto
+            // fixo são duplicadas
+            let mut formato = String::new();
+            for parte in partes {
+                match parte {
+                    ParteTexto::Texto(texto) => {
+                        formato.push_str(&escapar_string_para_rust(texto).replace('{', "{{").replace('}', "}}"));
+                    },
+                    ParteTexto::Expressao(_) => formato.push_str("{}"),
+                }
+            }
+            
+            write!(saida, "format!(\"{}\"", formato)?;
+            for parte in partes {
+                if let ParteTexto::Expressao(expr) = parte {
+                    write!(saida, ", ")?;
+                    gerar_exibicao(expr, contexto, saida)?;
+                }
+            }
+            write!(saida, ")")?;
+        },
+        Expressao::InteiroLiteral(num) => write!(saida, "{}i64", num)?,
+        // `{:?}` sempre inclui o ponto decimal (1.0), mantendo o literal como f64
+        Expressao::NumeroLiteral(num) => write!(saida, "{:?}", num)?,
         Expressao::LogicoLiteral(bool) => write!(saida, "{}", bool)?,
         Expressao::Nada => write!(saida, "None")?,
         Expressao::ListaLiteral(elementos) => {
             write!(saida, "vec![")?;
             for (i, elem) in elementos.iter().enumerate() {
                 if i > 0 { write!(saida, ", ")? }
-                gerar_expressao(elem, saida)?;
+                gerar_expressao(elem, contexto, saida)?;
             }
             write!(saida, "]")?
         },
@@ -215,29 +672,77 @@ fn gerar_expressao(expr: &Expressao, saida: &mut String) -> Result<()> {
             for (i, (chave, valor)) in pares.iter().enumerate() {
                 if i > 0 { write!(saida, ", ")? }
                 write!(saida, "(")?;
-                gerar_expressao(chave, saida)?;
+                gerar_expressao(chave, contexto, saida)?;
                 write!(saida, ", ")?;
-                gerar_expressao(valor, saida)?;
+                gerar_expressao(valor, contexto, saida)?;
                 write!(saida, ")")?;
             }
             write!(saida, "])")?
         },
-        Expressao::Identificador(nome) => write!(saida, "{}", nome)?,
+        Expressao::Identificador(nome) => match contexto.usar(nome) {
+            // Uma variável que guarda uma função é copiada (só o `Rc`), para
+            // que possa ser passada adiante mais de uma vez
+            Some(Some(Tipo::Funcao(..))) => write!(saida, "{}.clone()", identificador_rust(nome))?,
+            Some(_) => write!(saida, "{}", identificador_rust(nome))?,
+            // Uma função declarada usada como valor: `aplicar(dobro, 2)`
+            None if contexto.funcoes.contains_key(nome) => {
+                write!(saida, "std::rc::Rc::new({})", identificador_rust(nome))?;
+                // O tipo de uma função genérica depende do uso
+                if let Some(funcao) = contexto.funcoes.get(nome).filter(|funcao| funcao.genericos.is_empty()) {
+                    write!(saida, " as ")?;
+                    gerar_tipo(&funcao.tipo(), saida)?;
+                }
+            },
+            None => write!(saida, "{}", identificador_rust(nome))?,
+        },
         Expressao::Chamada { nome, argumentos } => {
-            write!(saida, "{}(", nome)?;
-            for (i, arg) in argumentos.iter().enumerate() {
-                if i > 0 { write!(saida, ", ")? }
-                gerar_expressao(arg, saida)?;
-            }
+            write!(saida, "{}", identificador_rust(nome))?;
+            let parametros = match contexto.usar(nome) {
+                Some(Some(Tipo::Funcao(parametros, _))) => parametros,
+                Some(_) => Vec::new(),
+                None => match (contexto.funcoes.get(nome), contexto.variantes.get(nome)) {
+                    (Some(funcao), _) => funcao.parametros.clone(),
+                    (None, Some((_, campos))) => campos.clone(),
+                    (None, None) => Vec::new(),
+                },
+            };
+            gerar_argumentos(argumentos, &parametros, contexto, saida)?;
+        },
+        Expressao::ChamadaMetodo { objeto, metodo, argumentos } => {
+            write!(saida, "(")?;
+            gerar_lugar(objeto, contexto, saida)?;
+            write!(saida, ").{}", identificador_rust(metodo))?;
+            let parametros = contexto.metodo(&contexto.tipo_de(objeto), metodo)
+                .map_or_else(Vec::new, |metodo| metodo.parametros.clone());
+            gerar_argumentos(argumentos, &parametros, contexto, saida)?;
+        },
+        Expressao::ChamadaExpressao { funcao, argumentos } => {
+            write!(saida, "(")?;
+            gerar_expressao(funcao, contexto, saida)?;
             write!(saida, ")")?;
+            let parametros = match contexto.tipo_de(funcao) {
+                Some(Tipo::Funcao(parametros, _)) => parametros,
+                _ => Vec::new(),
+            };
+            gerar_argumentos(argumentos, &parametros, contexto, saida)?;
         },
         Expressao::Negacao { expressao } => {
             write!(saida, "!")?;
-            gerar_expressao(expressao, saida)?;
+            gerar_expressao(expressao, contexto, saida)?;
+        },
+        Expressao::Negativo { expressao } => {
+            write!(saida, "(-")?;
+            gerar_expressao(expressao, contexto, saida)?;
+            write!(saida, ")")?;
         },
         Expressao::Operacao { operador, esquerda, direita } => {
+            // Com um `número` de um lado, o inteiro do outro vira `f64`
+            let numero = Some(Tipo::Numero);
+            let (tipo_esquerda, tipo_direita) = (contexto.tipo_de(esquerda), contexto.tipo_de(direita));
+            let esperado = |outro: &Option<Tipo>| numero.as_ref().filter(|_| eh_numerico(operador) && *outro == numero);
+            
             write!(saida, "(")?;
-            gerar_expressao(esquerda, saida)?;
+            gerar_valor(esquerda, esperado(&tipo_direita), contexto, saida)?;
             
             match operador {
                 Operador::Soma => write!(saida, " + ")?,
@@ -256,36 +761,1244 @@ fn gerar_expressao(expr: &Expressao, saida: &mut String) -> Result<()> {
                 Operador::Ou => write!(saida, " || ")?,
             }
             
-            gerar_expressao(direita, saida)?;
+            gerar_valor(direita, esperado(&tipo_esquerda), contexto, saida)?;
             if *operador == Operador::Contem {
                 write!(saida, ")")?;
             }
             write!(saida, ")")?;
         },
+        // Cada operando é guardado numa referência, avaliado só quando a
+        // comparação anterior vale: `{ let c0 = &(a); let c1 = &(b); c0 < c1 && ... }`
+        Expressao::Comparacao { operandos, operadores } => {
+            let numero = operandos.iter().any(|operando| contexto.tipo_de(operando) == Some(Tipo::Numero));
+            let esperado = if numero { Some(&Tipo::Numero) } else { None };
+            write!(saida, "{{ let __comparado0 = &(")?;
+            gerar_valor(&operandos[0], esperado, contexto, saida)?;
+            write!(saida, "); ")?;
+            for (i, (operador, operando)) in operadores.iter().zip(&operandos[1..]).enumerate() {
+                if i > 0 {
+                    write!(saida, " && {{ ")?;
+                }
+                write!(saida, "let __comparado{} = &(", i + 1)?;
+                gerar_valor(operando, esperado, contexto, saida)?;
+                let simbolo = match operador {
+                    Operador::Maior => ">",
+                    Operador::Menor => "<",
+                    Operador::MaiorIgual => ">=",
+                    _ => "<=",
+                };
+                write!(saida, "); __comparado{} {} __comparado{}", i, simbolo, i + 1)?;
+            }
+            write!(saida, "{}", " }".repeat(operadores.len()))?;
+        },
         Expressao::Atribuicao { nome, valor } => {
-            write!(saida, "{} = ", nome)?;
-            gerar_expressao(valor, saida)?;
+            contexto.alterar(nome)?;
+            write!(saida, "{} = ", identificador_rust(nome))?;
+            gerar_valor(valor, contexto.buscar(nome).flatten().as_ref(), contexto, saida)?;
+        },
+        // Os campos de `eu` são copiados: o método só tem uma referência
+        Expressao::AcessoMembro { .. } if parte_de_eu(expr) => {
+            gerar_lugar(expr, contexto, saida)?;
+            write!(saida, ".clone()")?;
         },
         Expressao::AcessoMembro { objeto, membro } => {
-            gerar_expressao(objeto, saida)?;
-            write!(saida, ".{}", membro)?;
+            gerar_expressao(objeto, contexto, saida)?;
+            write!(saida, ".{}", identificador_rust(membro))?;
+        },
+        Expressao::AtribuicaoMembro { objeto, membro, valor } => {
+            gerar_lugar(objeto, contexto, saida)?;
+            write!(saida, ".{} = ", identificador_rust(membro))?;
+            let campo = contexto.campo(&contexto.tipo_de(objeto), membro);
+            gerar_valor(valor, campo.as_ref(), contexto, saida)?;
+        },
+        Expressao::Indice { alvo, indice } => {
+            write!(saida, "(")?;
+            gerar_expressao(alvo, contexto, saida)?;
+            write!(saida, ").indexar(")?;
+            gerar_expressao(indice, contexto, saida)?;
+            write!(saida, ")")?;
+        },
+        Expressao::Fatia { alvo, inicio, fim } => {
+            write!(saida, "(")?;
+            gerar_expressao(alvo, contexto, saida)?;
+            write!(saida, ").fatiar(")?;
+            for (i, limite) in [inicio, fim].into_iter().enumerate() {
+                if i > 0 { write!(saida, ", ")? }
+                match limite {
+                    Some(limite) => {
+                        write!(saida, "Some(")?;
+                        gerar_expressao(limite, contexto, saida)?;
+                        write!(saida, ")")?;
+                    },
+                    None => write!(saida, "None")?,
+                }
+            }
+            write!(saida, ")")?;
+        },
+        Expressao::NovaInstancia { modelo } => {
+            write!(saida, "{}::default()", identificador_rust(modelo))?;
+        },
+        Expressao::LiteralModelo { modelo, campos } => {
+            let declarado = contexto.modelos.get(modelo);
+            write!(saida, "{} {{ ", identificador_rust(modelo))?;
+            for (campo, valor) in campos {
+                // Um campo de tipo genérico recebe o valor como está: é dele
+                // que o Rust tira o tipo (`Caixa { valor: 1 }` é `Caixa<i64>`)
+                let generico = declarado.is_some_and(|declarado| {
+                    declarado.campos.iter().any(|(nome, tipo, _)| nome == campo && usa_generico(tipo, &declarado.genericos))
+                });
+                write!(saida, "{}: ", identificador_rust(campo))?;
+                if generico {
+                    gerar_expressao(valor, contexto, saida)?;
+                    write!(saida, ", ")?;
+                } else {
+                    write!(saida, "(")?;
+                    gerar_expressao(valor, contexto, saida)?;
+                    write!(saida, ").converter(), ")?;
+                }
+            }
+            // Os campos omitidos recebem o valor padrão do tipo
+            let completo = declarado.is_some_and(|declarado| {
+                declarado.campos.iter().all(|(nome, _, _)| campos.iter().any(|(campo, _)| campo == nome))
+            });
+            if !completo {
+                write!(saida, "..Default::default() ")?;
+            }
+            write!(saida, "}}")?;
+        },
+        Expressao::Eu => {
+            if !contexto.anonimas.borrow().is_empty() {
+                return Err(anyhow!("Uma função anônima não pode usar `eu`; guarde o valor que ela precisa numa variável"));
+            }
+            write!(saida, "self")?;
+        },
+        Expressao::FuncaoAnonima { parametros, tipo_retorno, corpo } => {
+            // O corpo é gerado antes, para saber quais variáveis de fora ele usa
+            contexto.anonimas.borrow_mut().push((contexto.quadros.borrow().len(), Vec::new()));
+            contexto.abrir_funcao(parametros, tipo_retorno);
+            let mut corpo_rust = String::new();
+            let gerado = gerar_declaracao(corpo, contexto, &mut corpo_rust);
+            contexto.fechar();
+            let capturas = contexto.anonimas.borrow_mut().pop().map(|(_, capturas)| capturas).unwrap_or_default();
+            gerado?;
+            
+            // As variáveis de fora entram na função como cópias
+            write!(saida, "{{ ")?;
+            for nome in capturas {
+                write!(saida, "let {0} = {0}.clone(); ", identificador_rust(&nome))?;
+            }
+            let mut nomes = Vec::new();
+            for (nome_param, tipo_param) in parametros {
+                let mut parametro = format!("{}: ", identificador_rust(nome_param));
+                gerar_tipo(tipo_param, &mut parametro)?;
+                nomes.push(parametro);
+            }
+            write!(saida, "std::rc::Rc::new(move |{}|", nomes.join(", "))?;
+            if let Some(tipo) = tipo_retorno {
+                write!(saida, " -> ")?;
+                gerar_tipo(tipo, saida)?;
+            }
+            writeln!(saida, " {{")?;
+            saida.push_str(&corpo_rust);
+            write!(saida, "}}) as ")?;
+            let tipos = parametros.iter().map(|(_, tipo)| tipo.clone()).collect();
+            gerar_tipo(&Tipo::Funcao(tipos, tipo_retorno.clone().map(Box::new)), saida)?;
+            write!(saida, " }}")?;
         },
-        _ => return Err(anyhow!("Tipo de expressão não suportado: {:?}", expr)),
     }
     
     Ok(())
 }
 
+/// `(expr).exibir()`, o texto de um valor como PBRLang o mostra. Sozinho,
+/// `nada` não tem um tipo que o Rust possa deduzir e vira `None::<()>`.
+fn gerar_exibicao(expr: &Expressao, contexto: &Contexto, saida: &mut String) -> Result<()> {
+    write!(saida, "(")?;
+    match expr {
+        Expressao::Nada => write!(saida, "None::<()>")?,
+        _ => gerar_expressao(expr, contexto, saida)?,
+    }
+    write!(saida, ").exibir()")?;
+    Ok(())
+}
+
+/// Gera `expr` onde se espera um valor do tipo `esperado`: um inteiro vira
+/// `f64` onde se espera um `número`, e um valor vira `Some` onde se espera
+/// um opcional
+fn gerar_valor(expr: &Expressao, esperado: Option<&Tipo>, contexto: &Contexto, saida: &mut String) -> Result<()> {
+    match (esperado, expr) {
+        (Some(Tipo::Numero), Expressao::InteiroLiteral(num)) => write!(saida, "{}.0", num)?,
+        (Some(Tipo::Texto), Expressao::TextoLiteral(texto)) => {
+            write!(saida, "\"{}\".to_string()", escapar_string_para_rust(texto))?;
+        },
+        (Some(Tipo::Numero), _) if contexto.tipo_de(expr) == Some(Tipo::Inteiro) => {
+            write!(saida, "(")?;
+            gerar_expressao(expr, contexto, saida)?;
+            write!(saida, " as f64)")?;
+        },
+        (Some(Tipo::Lista(item)), Expressao::ListaLiteral(itens)) => {
+            write!(saida, "vec![")?;
+            for (i, elem) in itens.iter().enumerate() {
+                if i > 0 { write!(saida, ", ")? }
+                gerar_valor(elem, Some(item), contexto, saida)?;
+            }
+            write!(saida, "]")?;
+        },
+        (Some(Tipo::Mapa(tipo_chave, tipo_valor)), Expressao::DicionarioLiteral(pares)) => {
+            write!(saida, "HashMap::from([")?;
+            for (i, (chave, valor)) in pares.iter().enumerate() {
+                if i > 0 { write!(saida, ", ")? }
+                write!(saida, "(")?;
+                gerar_valor(chave, Some(tipo_chave), contexto, saida)?;
+                write!(saida, ", ")?;
+                gerar_valor(valor, Some(tipo_valor), contexto, saida)?;
+                write!(saida, ")")?;
+            }
+            write!(saida, "])")?;
+        },
+        (Some(Tipo::Opcional(interno)), _)
+            if contexto.tipo_de(expr).is_some_and(|tipo| !matches!(tipo, Tipo::Opcional(_))) =>
+        {
+            write!(saida, "Some(")?;
+            gerar_valor(expr, Some(interno), contexto, saida)?;
+            write!(saida, ")")?;
+        },
+        _ => gerar_expressao(expr, contexto, saida)?,
+    }
+    Ok(())
+}
+
+/// Uma expressão como lugar da memória, sem cópias: o objeto de uma
+/// atribuição a campo ou de uma chamada de método
+fn gerar_lugar(expr: &Expressao, contexto: &Contexto, saida: &mut String) -> Result<()> {
+    match expr {
+        Expressao::AcessoMembro { objeto, membro } => {
+            gerar_lugar(objeto, contexto, saida)?;
+            write!(saida, ".{}", identificador_rust(membro))?;
+        },
+        _ => gerar_expressao(expr, contexto, saida)?,
+    }
+    Ok(())
+}
+
+/// Se a expressão é `eu` ou um campo dele (`eu.endereco.cidade`)
+fn parte_de_eu(expr: &Expressao) -> bool {
+    match expr {
+        Expressao::Eu => true,
+        Expressao::AcessoMembro { objeto, .. } => parte_de_eu(objeto),
+        _ => false,
+    }
+}
+
+/// `fn nome(parametros) -> tipo`, sem o corpo. Um contrato como tipo de
+/// parâmetro ou de retorno vira `impl Contrato`, que aceita qualquer modelo
+/// que o cumpra.
+fn gerar_assinatura(
+    nome: &str,
+    genericos: &[String],
+    receptor: Option<&str>,
+    parametros: &[(String, Tipo)],
+    tipo_retorno: &Option<Tipo>,
+    contexto: &Contexto,
+    saida: &mut String,
+) -> Result<()> {
+    let gerar_tipo_assinatura = |tipo: &Tipo, saida: &mut String| match tipo {
+        Tipo::Personalizado(nome) if contexto.contratos.assinaturas.contains_key(nome) => {
+            write!(saida, "impl {}", identificador_rust(nome)).map_err(anyhow::Error::from)
+        },
+        _ => gerar_tipo(tipo, saida),
+    };
+    
+    write!(saida, "fn {}{}(", identificador_rust(nome), parametros_tipo(genericos))?;
+    
+    // Parâmetros, depois do `self` dos métodos
+    let mut nomes: Vec<String> = receptor.into_iter().map(str::to_string).collect();
+    for (nome_param, tipo_param) in parametros {
+        let mut parametro = format!("{}: ", identificador_rust(nome_param));
+        gerar_tipo_assinatura(tipo_param, &mut parametro)?;
+        nomes.push(parametro);
+    }
+    write!(saida, "{})", nomes.join(", "))?;
+    
+    // Tipo de retorno
+    if let Some(tipo) = tipo_retorno {
+        write!(saida, " -> ")?;
+        gerar_tipo_assinatura(tipo, saida)?;
+    }
+    Ok(())
+}
+
+/// Restrições dos parâmetros de tipo: o que o código gerado faz com os
+/// valores (copiar, comparar, mostrar e interpolar)
+const RESTRICOES_GENERICOS: &str = "Clone + PartialEq + std::fmt::Debug + Exibir";
+
+/// `<T: Clone + ...>`, ou nada quando não há parâmetros de tipo
+fn parametros_tipo(genericos: &[String]) -> String {
+    if genericos.is_empty() {
+        return String::new();
+    }
+    let parametros: Vec<String> = genericos.iter()
+        .map(|generico| format!("{}: {}", identificador_rust(generico), RESTRICOES_GENERICOS))
+        .collect();
+    format!("<{}>", parametros.join(", "))
+}
+
+/// Nome do tipo qualquer da biblioteca padrão (`lista de coisa`)
+const COISA: &str = "coisa";
+
+/// Quando o programa não declara um tipo `coisa`, ele é um parâmetro de tipo
+/// implícito das funções que o usam na assinatura:
+/// `faça primeiro(l: lista de coisa) -> coisa` é `faça primeiro<coisa>(...)`
+fn declarar_coisa(decl: &mut Declaracao) {
+    if let Declaracao::Funcao { genericos, parametros, tipo_retorno, .. } = decl {
+        let coisa = [COISA.to_string()];
+        let usa = parametros.iter().map(|(_, tipo)| tipo).chain(tipo_retorno.iter())
+            .any(|tipo| usa_generico(tipo, &coisa));
+        if usa && !genericos.iter().any(|generico| generico == COISA) {
+            genericos.push(COISA.to_string());
+        }
+    }
+    for interna in declaracoes_internas(decl) {
+        declarar_coisa(interna);
+    }
+}
+
+/// `<T, U>`, para usar o modelo genérico como tipo
+fn argumentos_tipo(genericos: &[String]) -> String {
+    if genericos.is_empty() {
+        return String::new();
+    }
+    let argumentos: Vec<String> = genericos.iter().map(|generico| identificador_rust(generico)).collect();
+    format!("<{}>", argumentos.join(", "))
+}
+
+/// Se o tipo é, ou contém, um dos parâmetros de tipo
+fn usa_generico(tipo: &Tipo, genericos: &[String]) -> bool {
+    match tipo {
+        Tipo::Personalizado(nome) => genericos.contains(nome),
+        Tipo::Opcional(interno) | Tipo::Lista(interno) => usa_generico(interno, genericos),
+        Tipo::Mapa(chave, valor) => usa_generico(chave, genericos) || usa_generico(valor, genericos),
+        Tipo::Generico(_, argumentos) => argumentos.iter().any(|argumento| usa_generico(argumento, genericos)),
+        Tipo::Funcao(parametros, retorno) => {
+            parametros.iter().chain(retorno.as_deref()).any(|tipo| usa_generico(tipo, genericos))
+        },
+        _ => false,
+    }
+}
+
+/// Função de um bloco `impl`. Os métodos de um contrato usam o receptor
+/// definido para o contrato; os demais, o que o próprio corpo pede.
+fn gerar_metodo(metodo: &Declaracao, contexto: &Contexto, saida: &mut String) -> Result<()> {
+    let Declaracao::Metodo { modelo, nome, parametros, tipo_retorno, corpo, publico, documentacao } = metodo else {
+        return Err(anyhow!("Esperava um método, encontrou {:?}", metodo));
+    };
+    
+    gerar_documentacao(documentacao, saida)?;
+    let contrato = contexto.contratos.contrato_de(metodo);
+    if contrato.is_none() && *publico {
+        write!(saida, "pub ")?;
+    }
+    let receptor = contexto.receptor(contrato.unwrap_or(modelo), nome);
+    gerar_assinatura(nome, &[], Some(receptor), parametros, tipo_retorno, contexto, saida)?;
+    writeln!(saida, " {{")?;
+    contexto.abrir_funcao(parametros, tipo_retorno);
+    contexto.declarar("eu", Some(Tipo::Personalizado(modelo.clone())));
+    gerar_declaracao(corpo, contexto, saida)?;
+    contexto.fechar();
+    writeln!(saida, "}}")?;
+    Ok(())
+}
+
+/// Métodos de listas, mapas e textos que alteram o valor em Rust
+const METODOS_QUE_ALTERAM: &[&str] = &[
+    "push", "pop", "insert", "remove", "clear", "extend", "append", "truncate", "retain",
+    "sort", "sort_by", "reverse", "dedup", "drain", "swap", "push_str", "entry", "get_mut",
+    "iter_mut", "resize", "split_off", "swap_remove",
+];
+
+/// Métodos que alteram `eu`, como (modelo, método), e métodos de contratos
+/// que pedem `&mut self`, como (contrato, método). Um método altera `eu`
+/// quando atribui a um campo dele ou chama um método que o altera; como um
+/// método pode chamar outro declarado depois, a busca se repete até que
+/// nenhum método novo entre no conjunto.
+fn metodos_que_alteram_eu(corpos: &[(String, String, Declaracao)], contexto: &Contexto) -> HashSet<(String, String)> {
+    let mut alteram = HashSet::new();
+    loop {
+        let mut novos = alteram.clone();
+        for (modelo, nome, corpo) in corpos {
+            if altera_eu(modelo, corpo, &alteram, contexto) {
+                novos.insert((modelo.clone(), nome.clone()));
+            }
+        }
+        // Se uma implementação altera `eu`, o contrato pede `&mut self`, e
+        // todas as implementações dele também recebem `&mut self`
+        for ((modelo, nome), contrato) in &contexto.contratos.cumpridos {
+            if novos.contains(&(modelo.clone(), nome.clone())) {
+                novos.insert((contrato.clone(), nome.clone()));
+            }
+        }
+        for ((modelo, nome), contrato) in &contexto.contratos.cumpridos {
+            if novos.contains(&(contrato.clone(), nome.clone())) {
+                novos.insert((modelo.clone(), nome.clone()));
+            }
+        }
+        if novos.len() == alteram.len() {
+            return alteram;
+        }
+        alteram = novos;
+    }
+}
+
+/// Se o corpo de um método altera `eu`, direto ou por uma variável que
+/// aponta para ele ou para um de seus campos (`pense s = eu`), segundo os
+/// métodos que já se sabe que alteram `eu`
+fn altera_eu(modelo: &str, corpo: &Declaracao, alteram: &HashSet<(String, String)>, contexto: &Contexto) -> bool {
+    // Variáveis que apontam para `eu` ou para um campo dele, com o tipo
+    let mut apelidos: HashMap<String, Option<Tipo>> = HashMap::new();
+    visitar(&mut corpo.clone(), &mut |decl| {
+        if let Declaracao::Variavel { nome, valor: Some(valor), .. } = decl
+            && let Some(tipo) = lugar_em_eu(valor, modelo, &apelidos, contexto)
+        {
+            apelidos.insert(nome.clone(), tipo);
+        }
+    });
+    
+    declaracao_contem(corpo, &|expr| match expr {
+        Expressao::AtribuicaoMembro { objeto, .. } => lugar_em_eu(objeto, modelo, &apelidos, contexto).is_some(),
+        Expressao::ChamadaMetodo { objeto, metodo, .. } => match lugar_em_eu(objeto, modelo, &apelidos, contexto) {
+            Some(Some(Tipo::Personalizado(tipo) | Tipo::Generico(tipo, _)))
+                if contexto.modelos.contains_key(&tipo) || contexto.contratos.assinaturas.contains_key(&tipo) => {
+                alteram.contains(&(tipo, metodo.clone()))
+            },
+            Some(_) => METODOS_QUE_ALTERAM.contains(&metodo.as_str()),
+            None => false,
+        },
+        _ => false,
+    })
+}
+
+/// O tipo de `expr`, quando ela é `eu`, um campo dele ou uma variável que
+/// aponta para um deles
+fn lugar_em_eu(
+    expr: &Expressao,
+    modelo: &str,
+    apelidos: &HashMap<String, Option<Tipo>>,
+    contexto: &Contexto,
+) -> Option<Option<Tipo>> {
+    match expr {
+        Expressao::Eu => Some(Some(Tipo::Personalizado(modelo.to_string()))),
+        Expressao::Identificador(nome) => apelidos.get(nome).cloned(),
+        Expressao::AcessoMembro { objeto, membro } => {
+            let tipo = lugar_em_eu(objeto, modelo, apelidos, contexto)?;
+            Some(contexto.campo(&tipo, membro))
+        },
+        _ => None,
+    }
+}
+
+/// O que a geração de código precisa saber sobre o programa inteiro
+struct Contexto {
+    contratos: Contratos,
+    modelos: HashMap<String, ModeloDeclarado>,
+    funcoes: HashMap<String, FuncaoDeclarada>,
+    // (modelo, método) -> assinatura
+    metodos: HashMap<(String, String), FuncaoDeclarada>,
+    // variante -> (enumeração, tipos dos campos)
+    variantes: HashMap<String, (String, Vec<Tipo>)>,
+    // (modelo, método) e (contrato, método) que recebem `&mut self`
+    alteram_eu: HashSet<(String, String)>,
+    // Nomes visíveis no ponto em que o código está sendo gerado, do quadro
+    // mais externo ao mais interno
+    quadros: RefCell<Vec<Quadro>>,
+    // Funções anônimas cujo corpo está sendo gerado, da mais externa à mais
+    // interna: quantos quadros estão fora dela e as variáveis de fora que
+    // ela usa
+    anonimas: RefCell<Vec<(usize, Vec<String>)>>,
+}
+
+struct ModeloDeclarado {
+    genericos: Vec<String>,
+    campos: Vec<(String, Tipo, bool)>,
+}
+
+struct FuncaoDeclarada {
+    genericos: Vec<String>,
+    parametros: Vec<Tipo>,
+    retorno: Option<Tipo>,
+}
+
+impl FuncaoDeclarada {
+    fn nova(genericos: &[String], parametros: &[(String, Tipo)], retorno: &Option<Tipo>) -> Self {
+        FuncaoDeclarada {
+            genericos: genericos.to_vec(),
+            parametros: parametros.iter().map(|(_, tipo)| tipo.clone()).collect(),
+            retorno: retorno.clone(),
+        }
+    }
+    
+    /// O tipo da função como valor
+    fn tipo(&self) -> Tipo {
+        Tipo::Funcao(self.parametros.clone(), self.retorno.clone().map(Box::new))
+    }
+    
+    /// O tipo de retorno, quando não depende dos parâmetros de tipo
+    fn retorno_conhecido(&self) -> Option<Tipo> {
+        self.retorno.clone().filter(|tipo| !usa_generico(tipo, &self.genericos))
+    }
+}
+
+/// Variáveis e parâmetros declarados num bloco, com o tipo quando ele é
+/// conhecido (declarado ou deduzido do valor)
+#[derive(Default)]
+struct Quadro {
+    nomes: HashMap<String, Option<Tipo>>,
+    // Tipo de retorno da função cujo corpo o quadro abre
+    retorno: Option<Option<Tipo>>,
+}
+
+impl Contexto {
+    fn novo(declaracoes: &mut [Declaracao]) -> Result<Self> {
+        let mut tipos = HashSet::new();
+        for declaracao in declaracoes.iter_mut() {
+            visitar(declaracao, &mut |decl| match decl {
+                Declaracao::Modelo { nome, .. } | Declaracao::Contrato { nome, .. }
+                | Declaracao::Enumeracao { nome, .. } => {
+                    tipos.insert(nome.clone());
+                },
+                _ => {},
+            });
+        }
+        if !tipos.contains(COISA) {
+            declaracoes.iter_mut().for_each(declarar_coisa);
+        }
+        
+        let contratos = Contratos::verificar(declaracoes)?;
+        let mut modelos = HashMap::new();
+        let mut funcoes = HashMap::new();
+        let mut metodos = HashMap::new();
+        let mut variantes = HashMap::new();
+        let mut corpos = Vec::new();
+        for declaracao in declaracoes.iter_mut() {
+            visitar(declaracao, &mut |decl| match decl {
+                Declaracao::Modelo { nome, genericos, campos, .. } => {
+                    modelos.insert(nome.clone(), ModeloDeclarado { genericos: genericos.clone(), campos: campos.clone() });
+                },
+                Declaracao::Funcao { nome, genericos, parametros, tipo_retorno, .. } => {
+                    funcoes.insert(nome.clone(), FuncaoDeclarada::nova(genericos, parametros, tipo_retorno));
+                },
+                Declaracao::Metodo { modelo, nome, parametros, tipo_retorno, corpo, .. } => {
+                    metodos.insert((modelo.clone(), nome.clone()), FuncaoDeclarada::nova(&[], parametros, tipo_retorno));
+                    corpos.push((modelo.clone(), nome.clone(), corpo.as_ref().clone()));
+                },
+                Declaracao::Enumeracao { nome, variantes: declaradas, .. } => {
+                    for (variante, campos) in declaradas.iter() {
+                        let tipos = campos.iter().map(|(_, tipo)| tipo.clone()).collect();
+                        variantes.insert(variante.clone(), (nome.clone(), tipos));
+                    }
+                },
+                _ => {},
+            });
+        }
+        
+        let mut contexto = Contexto {
+            contratos,
+            modelos,
+            funcoes,
+            metodos,
+            variantes,
+            alteram_eu: HashSet::new(),
+            quadros: RefCell::new(Vec::new()),
+            anonimas: RefCell::new(Vec::new()),
+        };
+        contexto.alteram_eu = metodos_que_alteram_eu(&corpos, &contexto);
+        Ok(contexto)
+    }
+    
+    /// O receptor de um método de um modelo ou de um contrato
+    fn receptor(&self, dono: &str, metodo: &str) -> &'static str {
+        if self.alteram_eu.contains(&(dono.to_string(), metodo.to_string())) {
+            "&mut self"
+        } else {
+            "&self"
+        }
+    }
+    
+    fn abrir(&self, quadro: Quadro) {
+        self.quadros.borrow_mut().push(quadro);
+    }
+    
+    fn fechar(&self) {
+        self.quadros.borrow_mut().pop();
+    }
+    
+    /// Abre um quadro com os parâmetros de uma função
+    fn abrir_funcao(&self, parametros: &[(String, Tipo)], retorno: &Option<Tipo>) {
+        let nomes = parametros.iter().map(|(nome, tipo)| (nome.clone(), Some(tipo.clone()))).collect();
+        self.abrir(Quadro { nomes, retorno: Some(retorno.clone()) });
+    }
+    
+    fn declarar(&self, nome: &str, tipo: Option<Tipo>) {
+        if let Some(quadro) = self.quadros.borrow_mut().last_mut() {
+            quadro.nomes.insert(nome.to_string(), tipo);
+        }
+    }
+    
+    /// O tipo de uma variável visível: `None` se o nome não é de uma
+    /// variável, `Some(None)` se o tipo não é conhecido
+    fn buscar(&self, nome: &str) -> Option<Option<Tipo>> {
+        self.quadros.borrow().iter().rev().find_map(|quadro| quadro.nomes.get(nome).cloned())
+    }
+    
+    /// Busca uma variável usada no código. Se ela é de fora de funções
+    /// anônimas que estão sendo geradas, elas passam a guardar uma cópia dela.
+    fn usar(&self, nome: &str) -> Option<Option<Tipo>> {
+        let quadros = self.quadros.borrow();
+        let (quadro, tipo) = quadros.iter().enumerate().rev()
+            .find_map(|(i, quadro)| Some((i, quadro.nomes.get(nome)?.clone())))?;
+        for (fora, capturas) in self.anonimas.borrow_mut().iter_mut() {
+            if quadro < *fora && !capturas.iter().any(|capturada| capturada == nome) {
+                capturas.push(nome.to_string());
+            }
+        }
+        Some(tipo)
+    }
+    
+    /// Verifica uma atribuição a uma variável. Uma função anônima guarda
+    /// cópias das variáveis de fora, feitas quando é criada, e por isso não
+    /// pode alterá-las: a alteração não seria vista fora.
+    fn alterar(&self, nome: &str) -> Result<()> {
+        let quadro = self.quadros.borrow().iter().rposition(|quadro| quadro.nomes.contains_key(nome));
+        let de_fora = quadro.is_some_and(|quadro| self.anonimas.borrow().iter().any(|(fora, _)| quadro < *fora));
+        if de_fora {
+            return Err(anyhow!("Uma função anônima não pode alterar `{}`, que é de fora dela", nome));
+        }
+        Ok(())
+    }
+    
+    /// O tipo de retorno da função em que o código está
+    fn retorno(&self) -> Option<Tipo> {
+        self.quadros.borrow().iter().rev().find_map(|quadro| quadro.retorno.clone()).flatten()
+    }
+    
+    fn campo(&self, tipo: &Option<Tipo>, membro: &str) -> Option<Tipo> {
+        let Some(Tipo::Personalizado(modelo) | Tipo::Generico(modelo, _)) = tipo else {
+            return None;
+        };
+        let declarado = self.modelos.get(modelo)?;
+        declarado.campos.iter()
+            .find(|(nome, _, _)| nome == membro)
+            .map(|(_, tipo, _)| tipo.clone())
+            .filter(|tipo| !usa_generico(tipo, &declarado.genericos))
+    }
+    
+    fn metodo(&self, tipo: &Option<Tipo>, metodo: &str) -> Option<&FuncaoDeclarada> {
+        let Some(Tipo::Personalizado(modelo) | Tipo::Generico(modelo, _)) = tipo else {
+            return None;
+        };
+        self.metodos.get(&(modelo.clone(), metodo.to_string()))
+    }
+    
+    /// O tipo de uma expressão, quando ele pode ser deduzido sem ambiguidade
+    fn tipo_de(&self, expr: &Expressao) -> Option<Tipo> {
+        match expr {
+            Expressao::TextoLiteral(_) | Expressao::TextoInterpolado(_) => Some(Tipo::Texto),
+            Expressao::InteiroLiteral(_) => Some(Tipo::Inteiro),
+            Expressao::NumeroLiteral(_) => Some(Tipo::Numero),
+            Expressao::LogicoLiteral(_) | Expressao::Negacao { .. } | Expressao::Comparacao { .. } => Some(Tipo::Logico),
+            Expressao::ListaLiteral(itens) => Some(Tipo::Lista(Box::new(self.tipo_de(itens.first()?)?))),
+            Expressao::Identificador(nome) => match self.buscar(nome) {
+                Some(tipo) => tipo,
+                None => self.funcoes.get(nome).map(FuncaoDeclarada::tipo),
+            },
+            Expressao::Chamada { nome, .. } => match self.buscar(nome) {
+                Some(Some(Tipo::Funcao(_, retorno))) => retorno.map(|tipo| *tipo),
+                Some(_) => None,
+                None => match (self.funcoes.get(nome), self.variantes.get(nome)) {
+                    (Some(funcao), _) => funcao.retorno_conhecido(),
+                    (None, Some((enumeracao, _))) => Some(Tipo::Personalizado(enumeracao.clone())),
+                    (None, None) => None,
+                },
+            },
+            Expressao::ChamadaMetodo { objeto, metodo, .. } => {
+                self.metodo(&self.tipo_de(objeto), metodo)?.retorno.clone()
+            },
+            Expressao::Negativo { expressao } => self.tipo_de(expressao),
+            Expressao::Operacao { operador, esquerda, direita } => {
+                if !eh_aritmetico(operador) {
+                    return Some(Tipo::Logico);
+                }
+                match (self.tipo_de(esquerda)?, self.tipo_de(direita)?) {
+                    (Tipo::Numero, Tipo::Numero | Tipo::Inteiro) | (Tipo::Inteiro, Tipo::Numero) => Some(Tipo::Numero),
+                    (Tipo::Inteiro, Tipo::Inteiro) => Some(Tipo::Inteiro),
+                    (Tipo::Texto, _) if *operador == Operador::Soma => Some(Tipo::Texto),
+                    _ => None,
+                }
+            },
+            Expressao::AcessoMembro { objeto, membro } => self.campo(&self.tipo_de(objeto), membro),
+            Expressao::Indice { alvo, .. } => match self.tipo_de(alvo)? {
+                Tipo::Lista(item) => Some(*item),
+                Tipo::Mapa(_, valor) => Some(*valor),
+                Tipo::Texto => Some(Tipo::Texto),
+                _ => None,
+            },
+            Expressao::Fatia { alvo, .. } => self.tipo_de(alvo),
+            Expressao::NovaInstancia { modelo } | Expressao::LiteralModelo { modelo, .. } => {
+                Some(Tipo::Personalizado(modelo.clone()))
+            },
+            Expressao::Eu => self.buscar("eu").flatten(),
+            Expressao::FuncaoAnonima { parametros, tipo_retorno, .. } => Some(Tipo::Funcao(
+                parametros.iter().map(|(_, tipo)| tipo.clone()).collect(),
+                tipo_retorno.clone().map(Box::new),
+            )),
+            _ => None,
+        }
+    }
+    
+    /// Se o tipo pode anotar uma variável em Rust: contratos viram
+    /// `impl Contrato` e modelos genéricos precisam dos argumentos
+    fn anotavel(&self, tipo: &Tipo) -> bool {
+        match tipo {
+            Tipo::Personalizado(nome) => {
+                !self.contratos.assinaturas.contains_key(nome)
+                    && self.modelos.get(nome).is_none_or(|modelo| modelo.genericos.is_empty())
+            },
+            Tipo::Opcional(interno) | Tipo::Lista(interno) => self.anotavel(interno),
+            Tipo::Mapa(chave, valor) => self.anotavel(chave) && self.anotavel(valor),
+            Tipo::Generico(_, argumentos) => argumentos.iter().all(|argumento| self.anotavel(argumento)),
+            Tipo::Funcao(parametros, retorno) => {
+                parametros.iter().chain(retorno.as_deref()).all(|tipo| self.anotavel(tipo))
+            },
+            _ => true,
+        }
+    }
+}
+
+/// Operadores cujo resultado é um número (os demais dão um lógico)
+fn eh_aritmetico(operador: &Operador) -> bool {
+    matches!(
+        operador,
+        Operador::Soma | Operador::Subtracao | Operador::Multiplicacao | Operador::Divisao | Operador::Resto
+    )
+}
+
+/// Operadores que comparam ou combinam dois números
+fn eh_numerico(operador: &Operador) -> bool {
+    eh_aritmetico(operador) || matches!(
+        operador,
+        Operador::Igual | Operador::Diferente | Operador::Maior | Operador::Menor
+            | Operador::MaiorIgual | Operador::MenorIgual
+    )
+}
+
+/// Contratos do programa e os métodos dos modelos que os cumprem. Em Rust,
+/// um modelo cumpre um contrato em um único `impl`, que reúne os métodos
+/// declarados dentro e fora do modelo.
+#[derive(Default)]
+struct Contratos {
+    // contrato -> assinaturas
+    assinaturas: HashMap<String, Vec<Assinatura>>,
+    // (modelo, contrato) -> métodos, na ordem das assinaturas
+    implementacoes: HashMap<(String, String), Vec<Declaracao>>,
+    // (modelo, método) -> contrato do qual o método faz parte
+    cumpridos: HashMap<(String, String), String>,
+}
+
+impl Contratos {
+    /// Reúne os contratos e verifica se cada modelo tem, com os mesmos
+    /// tipos, os métodos dos contratos que diz cumprir
+    fn verificar(declaracoes: &mut [Declaracao]) -> Result<Self> {
+        let mut contratos = Contratos::default();
+        let mut modelos: Vec<(String, Vec<String>)> = Vec::new();
+        let mut metodos: HashMap<String, Vec<Declaracao>> = HashMap::new();
+        
+        for declaracao in declaracoes.iter_mut() {
+            visitar(declaracao, &mut |decl| match decl {
+                Declaracao::Contrato { nome, metodos, .. } => {
+                    contratos.assinaturas.insert(nome.clone(), metodos.clone());
+                },
+                Declaracao::Modelo { nome, contratos, .. } => modelos.push((nome.clone(), contratos.clone())),
+                Declaracao::Metodo { modelo, .. } => metodos.entry(modelo.clone()).or_default().push(decl.clone()),
+                _ => {},
+            });
+        }
+        
+        for (modelo, cumpridos) in modelos {
+            for contrato in cumpridos {
+                let assinaturas = contratos.assinaturas.get(&contrato)
+                    .ok_or_else(|| anyhow!("O modelo `{}` cumpre `{}`, que não é um contrato", modelo, contrato))?;
+                
+                let mut implementacao = Vec::new();
+                for assinatura in assinaturas {
+                    let metodo = metodos.get(&modelo).into_iter().flatten()
+                        .find(|metodo| matches!(metodo, Declaracao::Metodo { nome, .. } if *nome == assinatura.nome));
+                    let Some(encontrado @ Declaracao::Metodo { nome, parametros, tipo_retorno, .. }) = metodo else {
+                        return Err(anyhow!(
+                            "O modelo `{}` cumpre `{}`, mas não tem o método `{}`",
+                            modelo, contrato, assinatura.nome
+                        ));
+                    };
+                    
+                    let tipos = |parametros: &[(String, Tipo)]| parametros.iter().map(|(_, tipo)| tipo.clone()).collect::<Vec<_>>();
+                    if tipos(parametros) != tipos(&assinatura.parametros) || *tipo_retorno != assinatura.tipo_retorno {
+                        return Err(anyhow!(
+                            "O método `{}.{}` não tem os mesmos tipos que a sua assinatura em `{}`",
+                            modelo, nome, contrato
+                        ));
+                    }
+                    
+                    contratos.cumpridos.insert((modelo.clone(), nome.clone()), contrato.clone());
+                    implementacao.push(encontrado.clone());
+                }
+                contratos.implementacoes.insert((modelo.clone(), contrato), implementacao);
+            }
+        }
+        
+        Ok(contratos)
+    }
+    
+    fn contrato_de(&self, metodo: &Declaracao) -> Option<&str> {
+        let Declaracao::Metodo { modelo, nome, .. } = metodo else {
+            return None;
+        };
+        self.cumpridos.get(&(modelo.clone(), nome.clone())).map(String::as_str)
+    }
+    
+    fn implementacao(&self, modelo: &str, contrato: &str) -> &[Declaracao] {
+        self.implementacoes.get(&(modelo.to_string(), contrato.to_string()))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Se alguma expressão de `decl`, inclusive as internas, satisfaz `teste`
+fn declaracao_contem(decl: &Declaracao, teste: &dyn Fn(&Expressao) -> bool) -> bool {
+    let expressoes: Vec<&Expressao> = match decl {
+        Declaracao::Variavel { valor, .. } => valor.iter().collect(),
+        Declaracao::Expressao(expr) | Declaracao::Mostrar(expr) => vec![expr],
+        Declaracao::Retorno(valor) => valor.iter().collect(),
+        Declaracao::Se { condicao, .. } | Declaracao::Enquanto { condicao, .. }
+        | Declaracao::Repita { condicao, .. } => vec![condicao],
+        Declaracao::Escolha { valor, casos, .. } => std::iter::once(valor)
+            .chain(casos.iter().flat_map(|(valores, _)| valores).filter_map(|padrao| match padrao {
+                Padrao::Valor(valor) => Some(valor),
+                Padrao::Variante { .. } => None,
+            }))
+            .collect(),
+        Declaracao::ParaCada { inicio, fim, passo, .. } => [inicio, fim].into_iter().chain(passo).collect(),
+        Declaracao::ParaCadaEm { colecao, .. } | Declaracao::ParaCadaPar { colecao, .. } => vec![colecao],
+        _ => Vec::new(),
+    };
+    if expressoes.into_iter().any(|expr| expressao_contem(expr, teste)) {
+        return true;
+    }
+    
+    let internas: Vec<&Declaracao> = match decl {
+        Declaracao::Bloco(declaracoes) => declaracoes.iter().collect(),
+        Declaracao::Se { bloco_se, bloco_senao, .. } => std::iter::once(bloco_se.as_ref()).chain(bloco_senao.as_deref()).collect(),
+        Declaracao::Escolha { casos, padrao, .. } => casos.iter().map(|(_, bloco)| bloco).chain(padrao.as_deref()).collect(),
+        Declaracao::ParaCada { corpo, .. } | Declaracao::ParaCadaEm { corpo, .. }
+        | Declaracao::ParaCadaPar { corpo, .. } | Declaracao::Enquanto { corpo, .. }
+        | Declaracao::Repita { corpo, .. } => vec![corpo.as_ref()],
+        Declaracao::QuandoDerErro { bloco_try, bloco_catch, .. } => vec![bloco_try.as_ref(), bloco_catch.as_ref()],
+        _ => Vec::new(),
+    };
+    internas.into_iter().any(|interna| declaracao_contem(interna, teste))
+}
+
+fn expressao_contem(expr: &Expressao, teste: &dyn Fn(&Expressao) -> bool) -> bool {
+    if teste(expr) {
+        return true;
+    }
+    if let Expressao::FuncaoAnonima { corpo, .. } = expr {
+        return declaracao_contem(corpo, teste);
+    }
+    let internas: Vec<&Expressao> = match expr {
+        Expressao::TextoInterpolado(partes) => partes.iter().filter_map(|parte| match parte {
+            ParteTexto::Expressao(expr) => Some(expr),
+            ParteTexto::Texto(_) => None,
+        }).collect(),
+        Expressao::ListaLiteral(itens) => itens.iter().collect(),
+        Expressao::DicionarioLiteral(pares) => pares.iter().flat_map(|(chave, valor)| [chave, valor]).collect(),
+        Expressao::Chamada { argumentos, .. } => argumentos.iter().collect(),
+        Expressao::ChamadaMetodo { objeto: alvo, argumentos, .. }
+        | Expressao::ChamadaExpressao { funcao: alvo, argumentos } => {
+            std::iter::once(alvo.as_ref()).chain(argumentos).collect()
+        },
+        Expressao::Operacao { esquerda, direita, .. } => vec![esquerda, direita],
+        Expressao::Comparacao { operandos, .. } => operandos.iter().collect(),
+        Expressao::Atribuicao { valor, .. } => vec![valor],
+        Expressao::AtribuicaoMembro { objeto, valor, .. } => vec![objeto, valor],
+        Expressao::AcessoMembro { objeto, .. } => vec![objeto],
+        Expressao::Indice { alvo, indice } => vec![alvo, indice],
+        Expressao::Fatia { alvo, inicio, fim } => std::iter::once(alvo.as_ref()).chain(inicio.as_deref()).chain(fim.as_deref()).collect(),
+        Expressao::Negacao { expressao } | Expressao::Negativo { expressao } => vec![expressao],
+        Expressao::LiteralModelo { campos, .. } => campos.iter().map(|(_, valor)| valor).collect(),
+        _ => Vec::new(),
+    };
+    internas.into_iter().any(|interna| expressao_contem(interna, teste))
+}
+
+/// Braço de um `match` para os valores de um caso. Inteiros e lógicos
+/// viram padrões (`1i64 | 2i64`); os demais, como números `f64`, textos e
+/// expressões, não podem ser padrões em Rust e são comparados numa guarda.
+fn gerar_caso(valores: &[Padrao], contexto: &Contexto, saida: &mut String) -> Result<()> {
+    let eh_padrao = |valor: &Padrao| match valor {
+        Padrao::Variante { .. } => true,
+        Padrao::Valor(Expressao::InteiroLiteral(_) | Expressao::LogicoLiteral(_)) => true,
+        Padrao::Valor(Expressao::Negativo { expressao }) => matches!(**expressao, Expressao::InteiroLiteral(_)),
+        Padrao::Valor(_) => false,
+    };
+    
+    if valores.iter().all(eh_padrao) {
+        for (i, valor) in valores.iter().enumerate() {
+            if i > 0 { write!(saida, " | ")? }
+            match valor {
+                Padrao::Variante { nome, campos } => {
+                    write!(saida, "{}", identificador_rust(nome))?;
+                    if !campos.is_empty() {
+                        let nomes: Vec<String> = campos.iter()
+                            .map(|c| if c == "_" { c.clone() } else { identificador_rust(c) })
+                            .collect();
+                        write!(saida, "({})", nomes.join(", "))?;
+                    }
+                },
+                Padrao::Valor(Expressao::Negativo { expressao }) => {
+                    write!(saida, "-")?;
+                    gerar_expressao(expressao, contexto, saida)?;
+                },
+                Padrao::Valor(valor) => gerar_expressao(valor, contexto, saida)?,
+            }
+        }
+    } else {
+        write!(saida, "valor_escolhido if ")?;
+        for (i, valor) in valores.iter().enumerate() {
+            if i > 0 { write!(saida, " || ")? }
+            match valor {
+                Padrao::Valor(valor) => {
+                    write!(saida, "*valor_escolhido == ")?;
+                    gerar_expressao(valor, contexto, saida)?;
+                },
+                Padrao::Variante { nome, .. } => {
+                    return Err(anyhow!("A variante `{}` não pode estar no mesmo caso que outros valores", nome));
+                },
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Argumentos de uma chamada; `parametros` são os tipos esperados, quando
+/// a função chamada é conhecida
+fn gerar_argumentos(argumentos: &[Expressao], parametros: &[Tipo], contexto: &Contexto, saida: &mut String) -> Result<()> {
+    write!(saida, "(")?;
+    for (i, arg) in argumentos.iter().enumerate() {
+        if i > 0 { write!(saida, ", ")? }
+        gerar_valor(arg, parametros.get(i), contexto, saida)?;
+    }
+    write!(saida, ")")?;
+    Ok(())
+}
+
+/// Implementa `Exibir` para um modelo, no formato `Pessoa { nome: Ana }`
+fn gerar_exibir_modelo(nome: &str, genericos: &[String], campos: &[(String, Tipo, bool)], saida: &mut String) -> Result<()> {
+    writeln!(
+        saida, "impl{} Exibir for {}{} {{",
+        parametros_tipo(genericos), identificador_rust(nome), argumentos_tipo(genericos)
+    )?;
+    writeln!(saida, "    fn exibir(&self) -> String {{")?;
+    
+    if campos.is_empty() {
+        writeln!(saida, "        \"{}\".to_string()", escapar_string_para_rust(nome))?;
+    } else {
+        let formato: Vec<String> = campos.iter()
+            .map(|(campo, _, _)| format!("{}: {{}}", escapar_string_para_rust(campo)))
+            .collect();
+        write!(saida, "        format!(\"{} {{{{ {} }}}}\"", escapar_string_para_rust(nome), formato.join(", "))?;
+        for (campo, _, _) in campos {
+            write!(saida, ", self.{}.exibir()", identificador_rust(campo))?;
+        }
+        writeln!(saida, ")")?;
+    }
+    
+    writeln!(saida, "    }}")?;
+    writeln!(saida, "}}")?;
+    Ok(())
+}
+
+/// Implementa `Exibir` para uma enumeração, no formato `Circulo(raio: 2)`
+fn gerar_exibir_enumeracao(nome: &str, variantes: &[(String, Vec<(String, Tipo)>)], saida: &mut String) -> Result<()> {
+    let tipo = identificador_rust(nome);
+    writeln!(saida, "impl Exibir for {} {{", tipo)?;
+    writeln!(saida, "    fn exibir(&self) -> String {{")?;
+    writeln!(saida, "        match self {{")?;
+    
+    for (variante, campos) in variantes {
+        write!(saida, "            {}::{}", tipo, identificador_rust(variante))?;
+        if campos.is_empty() {
+            writeln!(saida, " => \"{}\".to_string(),", escapar_string_para_rust(variante))?;
+            continue;
+        }
+        let nomes: Vec<String> = campos.iter().map(|(campo, _)| identificador_rust(campo)).collect();
+        let formato: Vec<String> = campos.iter()
+            .map(|(campo, _)| format!("{}: {{}}", escapar_string_para_rust(campo)))
+            .collect();
+        write!(saida, "({}) => format!(\"{}({})\"", nomes.join(", "), escapar_string_para_rust(variante), formato.join(", "))?;
+        for campo in &nomes {
+            write!(saida, ", {}.exibir()", campo)?;
+        }
+        writeln!(saida, "),")?;
+    }
+    
+    writeln!(saida, "        }}")?;
+    writeln!(saida, "    }}")?;
+    writeln!(saida, "}}")?;
+    Ok(())
+}
+
+/// Enumerações declaradas no programa, usadas para reconhecer e verificar
+/// os padrões dos casos de `escolha`
+#[derive(Default)]
+struct Enumeracoes {
+    // variante -> (enumeração, número de campos)
+    variantes: HashMap<String, (String, usize)>,
+    // enumeração -> variantes, na ordem da declaração
+    tipos: HashMap<String, Vec<String>>,
+}
+
+impl Enumeracoes {
+    fn coletar(&mut self, decl: &mut Declaracao) -> Result<()> {
+        if let Declaracao::Enumeracao { nome, variantes, .. } = decl {
+            for (variante, campos) in variantes.iter() {
+                if let Some((outro, _)) = self.variantes.get(variante) {
+                    return Err(anyhow!("A variante `{}` está em `{}` e em `{}`", variante, outro, nome));
+                }
+                self.variantes.insert(variante.clone(), (nome.clone(), campos.len()));
+            }
+            self.tipos.insert(nome.clone(), variantes.iter().map(|(v, _)| v.clone()).collect());
+        }
+        for interna in declaracoes_internas(decl) {
+            self.coletar(interna)?;
+        }
+        Ok(())
+    }
+    
+    /// Marca `caso Vazio` como variante quando `Vazio` não tem campos e
+    /// verifica se cada escolha sobre variantes trata todas elas
+    fn resolver_escolhas(&self, decl: &mut Declaracao) -> Result<()> {
+        if let Declaracao::Escolha { casos, padrao, .. } = decl {
+            let mut tipo: Option<&str> = None;
+            let mut tratadas = Vec::new();
+            let mut outros_valores = false;
+            
+            for padrao_caso in casos.iter_mut().flat_map(|(valores, _)| valores.iter_mut()) {
+                let sem_campos = match padrao_caso {
+                    Padrao::Valor(Expressao::Identificador(nome))
+                        if self.variantes.get(nome).is_some_and(|(_, campos)| *campos == 0) => Some(nome.clone()),
+                    _ => None,
+                };
+                if let Some(nome) = sem_campos {
+                    *padrao_caso = Padrao::Variante { nome, campos: Vec::new() };
+                }
+                
+                let Padrao::Variante { nome, campos } = padrao_caso else {
+                    outros_valores = true;
+                    continue;
+                };
+                let (dono, quantidade) = self.variantes.get(nome)
+                    .ok_or_else(|| anyhow!("`{}` não é uma variante de nenhum tipo", nome))?;
+                if campos.len() != *quantidade {
+                    return Err(anyhow!(
+                        "A variante `{}` tem {} campo(s), mas o caso dá nome a {}",
+                        nome, quantidade, campos.len()
+                    ));
+                }
+                if let Some(tipo) = tipo && tipo != dono {
+                    return Err(anyhow!("A escolha mistura variantes de `{}` e de `{}`", tipo, dono));
+                }
+                tipo = Some(dono);
+                tratadas.push(nome.clone());
+            }
+            
+            if let Some(tipo) = tipo {
+                if outros_valores {
+                    return Err(anyhow!("Os casos de uma escolha sobre `{}` devem ser variantes", tipo));
+                }
+                let faltando: Vec<String> = self.tipos[tipo].iter()
+                    .filter(|variante| !tratadas.contains(variante))
+                    .map(|variante| format!("`{}`", variante))
+                    .collect();
+                if padrao.is_none() && !faltando.is_empty() {
+                    return Err(anyhow!(
+                        "A escolha sobre `{}` não trata {}; acrescente os casos que faltam ou um `padrão`",
+                        tipo, faltando.join(", ")
+                    ));
+                }
+            }
+        }
+        for interna in declaracoes_internas(decl) {
+            self.resolver_escolhas(interna)?;
+        }
+        Ok(())
+    }
+}
+
+/// Chama `visita` para `decl` e para todas as declarações dentro dela
+fn visitar(decl: &mut Declaracao, visita: &mut dyn FnMut(&Declaracao)) {
+    visita(decl);
+    for interna in declaracoes_internas(decl) {
+        visitar(interna, visita);
+    }
+}
+
+/// Declarações contidas diretamente em `decl`
+fn declaracoes_internas(decl: &mut Declaracao) -> Vec<&mut Declaracao> {
+    match decl {
+        Declaracao::Bloco(declaracoes) | Declaracao::Modulo { declaracoes, .. }
+        | Declaracao::Modelo { metodos: declaracoes, .. } => declaracoes.iter_mut().collect(),
+        Declaracao::Se { bloco_se, bloco_senao, .. } => {
+            std::iter::once(bloco_se.as_mut()).chain(bloco_senao.as_deref_mut()).collect()
+        },
+        Declaracao::Escolha { casos, padrao, .. } => {
+            casos.iter_mut().map(|(_, bloco)| bloco).chain(padrao.as_deref_mut()).collect()
+        },
+        Declaracao::ParaCada { corpo, .. } | Declaracao::ParaCadaEm { corpo, .. }
+        | Declaracao::ParaCadaPar { corpo, .. } | Declaracao::Enquanto { corpo, .. }
+        | Declaracao::Repita { corpo, .. } | Declaracao::Funcao { corpo, .. }
+        | Declaracao::Metodo { corpo, .. } => vec![corpo.as_mut()],
+        Declaracao::QuandoDerErro { bloco_try, bloco_catch, .. } => vec![bloco_try.as_mut(), bloco_catch.as_mut()],
+        _ => Vec::new(),
+    }
+}
+
+fn gerar_documentacao(documentacao: &Option<String>, saida: &mut String) -> Result<()> {
+    if let Some(doc) = documentacao {
+        for linha in doc.lines() {
+            if linha.is_empty() {
+                writeln!(saida, "///")?;
+            } else {
+                writeln!(saida, "/// {}", linha)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 fn gerar_tipo(tipo: &Tipo, saida: &mut String) -> Result<()> {
     match tipo {
         Tipo::Texto => write!(saida, "String")?,
         Tipo::Numero => write!(saida, "f64")?,
+        Tipo::Inteiro => write!(saida, "i64")?,
         Tipo::Logico => write!(saida, "bool")?,
-        Tipo::Personalizado(nome) => write!(saida, "{}", nome)?,
+        Tipo::Personalizado(nome) => write!(saida, "{}", identificador_rust(nome))?,
+        Tipo::Void => write!(saida, "()")?,
+        Tipo::Opcional(interno) => {
+            write!(saida, "Option<")?;
+            gerar_tipo(interno, saida)?;
+            write!(saida, ">")?;
+        },
+        Tipo::Lista(item) => {
+            write!(saida, "Vec<")?;
+            gerar_tipo(item, saida)?;
+            write!(saida, ">")?;
+        },
+        Tipo::Mapa(chave, valor) => {
+            write!(saida, "HashMap<")?;
+            gerar_tipo(chave, saida)?;
+            write!(saida, ", ")?;
+            gerar_tipo(valor, saida)?;
+            write!(saida, ">")?;
+        },
+        Tipo::Generico(nome, argumentos) => {
+            write!(saida, "{}<", identificador_rust(nome))?;
+            for (i, argumento) in argumentos.iter().enumerate() {
+                if i > 0 { write!(saida, ", ")? }
+                gerar_tipo(argumento, saida)?;
+            }
+            write!(saida, ">")?;
+        },
+        // `Rc` deixa a função ser copiada e guardada em variáveis, listas e campos
+        Tipo::Funcao(parametros, retorno) => {
+            write!(saida, "std::rc::Rc<dyn Fn(")?;
+            for (i, parametro) in parametros.iter().enumerate() {
+                if i > 0 { write!(saida, ", ")? }
+                gerar_tipo(parametro, saida)?;
+            }
+            write!(saida, ")")?;
+            if let Some(retorno) = retorno {
+                write!(saida, " -> ")?;
+                gerar_tipo(retorno, saida)?;
+            }
+            write!(saida, ">")?;
+        },
     }
     Ok(())
 }
 
+/// Palavras reservadas do Rust que podem ser usadas como identificadores
+/// brutos (`r#nome`)
+const PALAVRAS_RESERVADAS_RUST: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override",
+    "priv", "pub", "ref", "return", "static", "struct", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Palavras reservadas que não aceitam a forma `r#`
+const PALAVRAS_RESERVADAS_SEM_FORMA_BRUTA: &[&str] = &["crate", "self", "Self", "super", "_"];
+
+/// Converte um identificador PBRLang em um identificador Rust válido e só
+/// com caracteres ASCII, de forma determinística:
+///
+/// - identificadores ASCII são mantidos (palavras reservadas do Rust viram
+///   `r#nome`, ou `nome_` quando a forma bruta não é permitida);
+/// - letras acentuadas perdem o acento (`ç` vira `c`) e outros caracteres
+///   viram `u` seguido do código hexadecimal; para que `número` e `numero`
+///   não colidam, é acrescentado um sufixo com o hash do nome original.
+pub fn identificador_rust(nome: &str) -> String {
+    if nome.is_ascii() {
+        if PALAVRAS_RESERVADAS_SEM_FORMA_BRUTA.contains(&nome) {
+            return format!("{}_", nome);
+        }
+        if PALAVRAS_RESERVADAS_RUST.contains(&nome) {
+            return format!("r#{}", nome);
+        }
+        return nome.to_string();
+    }
+    
+    let mut ascii = String::new();
+    for c in nome.chars() {
+        if c.is_ascii() {
+            ascii.push(c);
+            continue;
+        }
+        
+        let mut base = String::new();
+        let mut outros = false;
+        decompose_canonical(c, |d| {
+            if d.is_ascii() {
+                base.push(d);
+            } else if !is_combining_mark(d) {
+                outros = true;
+            }
+        });
+        
+        if base.is_empty() || outros {
+            ascii.push_str(&format!("u{:x}", c as u32));
+        } else {
+            ascii.push_str(&base);
+        }
+    }
+    
+    format!("{}_{:08x}", ascii, hash_fnv1a(nome))
+}
+
+/// Hash FNV-1a de 32 bits: simples e estável entre versões e plataformas
+fn hash_fnv1a(texto: &str) -> u32 {
+    texto.bytes().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
 fn escapar_string_para_rust(texto: &str) -> String {
     texto
         .replace("\\", "\\\\")
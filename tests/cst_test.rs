@@ -0,0 +1,74 @@
+use anyhow::Result;
+use pbrlang::cst::analisar_concreto;
+use pbrlang::lexer::{TipoTrivia, Token};
+use std::fs;
+
+#[test]
+fn test_cst_reproduz_arquivos_pbr() -> Result<()> {
+    let mut arquivos = 0;
+    
+    for pasta in ["lib", "examples"] {
+        for entrada in fs::read_dir(pasta)? {
+            let caminho = entrada?.path();
+            if caminho.extension().is_none_or(|e| e != "pbr") {
+                continue;
+            }
+            
+            let codigo = fs::read_to_string(&caminho)?;
+            let arvore = analisar_concreto(&codigo)?;
+            assert!(arvore.to_string() == codigo, "{} não foi reproduzido", caminho.display());
+            arquivos += 1;
+        }
+    }
+    
+    assert!(arquivos > 0, "Nenhum arquivo .pbr encontrado");
+    Ok(())
+}
+
+#[test]
+fn test_cst_reproduz_casos_especiais() -> Result<()> {
+    let casos = [
+        "",
+        "   \n\n",
+        "pense x = 1",
+        "pense  x\t=\t1 ;\r\nmostre x\r\n",
+        "/* a /* b */ c */ pense x = 1 // fim\n/// doc\nfaça f() {}\n\n// sobra",
+        "mostre \"olá ${nome}\\n\"  é   igual a  para\n  cada",
+        "pense me\u{301}dia = 1",
+        "\u{a0}pense x = 1\r",
+    ];
+    
+    for codigo in casos {
+        assert_eq!(analisar_concreto(codigo)?.to_string(), codigo);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_cst_divisao_da_trivia() -> Result<()> {
+    let codigo = "// início\npense x = 1; // um\n\n/* dois */\nmostre x\n";
+    let arvore = analisar_concreto(codigo)?;
+    let tokens = &arvore.tokens;
+    
+    // O primeiro token recebe os comentários do início do arquivo
+    assert_eq!(tokens[0].token.token, Token::Pense);
+    let antes: Vec<TipoTrivia> = tokens[0].trivia_antes.iter().map(|t| t.tipo).collect();
+    assert_eq!(antes, vec![TipoTrivia::ComentarioLinha, TipoTrivia::QuebraLinha]);
+    
+    // O comentário no fim da linha fica com o `;`, até a quebra de linha
+    let ponto_virgula = &tokens[4];
+    assert_eq!(ponto_virgula.texto, ";");
+    let depois: Vec<&str> = ponto_virgula.trivia_depois.iter().map(|t| t.texto).collect();
+    assert_eq!(depois, vec![" ", "// um", "\n"]);
+    
+    // As linhas seguintes pertencem ao próximo token
+    let mostre = &tokens[5];
+    let antes: Vec<&str> = mostre.trivia_antes.iter().map(|t| t.texto).collect();
+    assert_eq!(antes, vec!["\n", "/* dois */", "\n"]);
+    
+    // O texto do token é o trecho original, mesmo quando o token é normalizado
+    let arvore = analisar_concreto("me\u{301}dia")?;
+    assert_eq!(arvore.tokens[0].texto, "me\u{301}dia");
+    assert_eq!(arvore.tokens[0].token.token, Token::Identificador("média".into()));
+    Ok(())
+}
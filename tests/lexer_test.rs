@@ -0,0 +1,389 @@
+use anyhow::Result;
+use pbrlang::lexer::{tokenizar, tokenizar_com_recuperacao, Lexer, LexerError, Token};
+
+#[test]
+fn test_lexer_posicoes_dos_tokens() -> Result<()> {
+    let codigo = "pense idade = 42;\nmostre \"olá\";";
+    let tokens = tokenizar(codigo)?;
+    
+    // Cada span deve corresponder exatamente ao trecho do código fonte
+    let trechos: Vec<&str> = tokens.iter().map(|t| &codigo[t.inicio..t.fim]).collect();
+    assert_eq!(trechos, vec!["pense", "idade", "=", "42", ";", "mostre", "\"olá\"", ";", ""]);
+    
+    let mostre = &tokens[5];
+    assert_eq!(mostre.token, Token::Mostre);
+    assert_eq!((mostre.linha, mostre.coluna), (2, 1));
+    
+    // A coluna conta caracteres, não bytes
+    let ponto_virgula = &tokens[7];
+    assert_eq!((ponto_virgula.linha, ponto_virgula.coluna), (2, 13));
+    assert_eq!(tokens.last().map(|t| &t.token), Some(&Token::EOF));
+    
+    Ok(())
+}
+
+#[test]
+fn test_lexer_erro_com_posicao() {
+    let mut lexer = Lexer::new("pense x = 1;\n  x @ 2;");
+    
+    let erro = loop {
+        match lexer.proximo_token() {
+            Ok(t) if t.token == Token::EOF => panic!("Esperava um erro léxico"),
+            Ok(_) => continue,
+            Err(e) => break e,
+        }
+    };
+    
+    assert_eq!(erro, LexerError::CaractereInesperado {
+        caractere: '@',
+        inicio: 17,
+        fim: 18,
+        linha: 2,
+        coluna: 5,
+    });
+}
+
+fn somente_tokens(codigo: &str) -> Result<Vec<Token<'_>>> {
+    Ok(tokenizar(codigo)?.into_iter().map(|t| t.token).collect())
+}
+
+#[test]
+fn test_lexer_tokens_sem_copia() -> Result<()> {
+    use std::borrow::Cow;
+    
+    let codigo = "pense nome = \"Ana\" + \"a\\tb\" + média";
+    let tokens = somente_tokens(codigo)?;
+    
+    // Identificadores e textos sem escapes apontam para o código fonte
+    assert!(matches!(&tokens[1], Token::Identificador(Cow::Borrowed("nome"))));
+    assert!(matches!(&tokens[3], Token::TextoLiteral(Cow::Borrowed("Ana"))));
+    assert!(matches!(&tokens[7], Token::Identificador(Cow::Borrowed("média"))));
+    
+    // Só textos com escapes precisam de uma cópia decodificada
+    assert!(matches!(&tokens[5], Token::TextoLiteral(Cow::Owned(t)) if t == "a\tb"));
+    Ok(())
+}
+
+#[test]
+fn test_lexer_escapes_em_texto() -> Result<()> {
+    let tokens = somente_tokens(r#""linha\n\t\"aspas\" \\ \$ \u{e9}""#)?;
+    assert_eq!(tokens, vec![
+        Token::TextoLiteral("linha\n\t\"aspas\" \\ $ é".into()),
+        Token::EOF,
+    ]);
+    Ok(())
+}
+
+#[test]
+fn test_lexer_escape_invalido() {
+    let mut lexer = Lexer::new(r#""abc\qdef""#);
+    let erro = lexer.proximo_token().unwrap_err();
+    assert!(matches!(erro, LexerError::EscapeInvalido { ref sequencia, coluna: 5, .. } if sequencia == "\\q"));
+    
+    let mut lexer = Lexer::new(r#""\u{110000}""#);
+    assert!(matches!(lexer.proximo_token(), Err(LexerError::EscapeInvalido { .. })));
+}
+
+#[test]
+fn test_lexer_texto_multilinha_sem_escapes() -> Result<()> {
+    // Como na gramática, `"""` é lido como está: `\d` e `${` não são especiais
+    let tokens = somente_tokens("pense x = \"\"\"a\\d ${b}\n\"q\" \"\"\"; mostre x")?;
+    assert_eq!(tokens, vec![
+        Token::Pense,
+        Token::Identificador("x".into()),
+        Token::Igual,
+        Token::TextoLiteral("a\\d ${b}\n\"q\" ".into()),
+        Token::PontoVirgula,
+        Token::Mostre,
+        Token::Identificador("x".into()),
+        Token::EOF,
+    ]);
+    assert!(pbrlang::analisar_codigo("pense x = \"\"\"a\\dq\"\"\"").is_ok());
+    
+    let (_, erros) = tokenizar_com_recuperacao("mostre \"\"\"a\nb");
+    assert!(matches!(erros.as_slice(), [LexerError::StringNaoTerminada { linha: 1, coluna: 8, .. }]));
+    Ok(())
+}
+
+#[test]
+fn test_lexer_texto_interpolado() -> Result<()> {
+    let tokens = somente_tokens(r#"mostre "Olá, ${nome}! Você tem ${ f({a: 1}) } anos""#)?;
+    assert_eq!(tokens, vec![
+        Token::Mostre,
+        Token::TextoInicio("Olá, ".into()),
+        Token::Identificador("nome".into()),
+        Token::TextoMeio("! Você tem ".into()),
+        Token::Identificador("f".into()),
+        Token::AbreParentese,
+        Token::AbreChave,
+        Token::Identificador("a".into()),
+        Token::DoisPontos,
+        Token::InteiroLiteral(1),
+        Token::FechaChave,
+        Token::FechaParentese,
+        Token::TextoFim(" anos".into()),
+        Token::EOF,
+    ]);
+    Ok(())
+}
+
+#[test]
+fn test_lexer_interpolacao_aninhada() -> Result<()> {
+    let tokens = somente_tokens(r#""a${ "b${c}" }d""#)?;
+    assert_eq!(tokens, vec![
+        Token::TextoInicio("a".into()),
+        Token::TextoInicio("b".into()),
+        Token::Identificador("c".into()),
+        Token::TextoFim("".into()),
+        Token::TextoFim("d".into()),
+        Token::EOF,
+    ]);
+    
+    assert!(tokenizar(r#""a${b"#).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_lexer_literais_numericos() -> Result<()> {
+    let tokens = somente_tokens("42 1_000_000 0xFF 0b1010 0o17 2.5 1.5e-3 2E10 7e+2")?;
+    assert_eq!(tokens, vec![
+        Token::InteiroLiteral(42),
+        Token::InteiroLiteral(1_000_000),
+        Token::InteiroLiteral(255),
+        Token::InteiroLiteral(10),
+        Token::InteiroLiteral(15),
+        Token::NumeroLiteral(2.5),
+        Token::NumeroLiteral(1.5e-3),
+        Token::NumeroLiteral(2e10),
+        Token::NumeroLiteral(700.0),
+        Token::EOF,
+    ]);
+    Ok(())
+}
+
+#[test]
+fn test_lexer_literais_numericos_invalidos() {
+    for codigo in ["1_", "1__", "0xFG", "0b102", "12abc", "1.5e", "99999999999999999999", "1e999"] {
+        let resultado = Lexer::new(codigo).proximo_token();
+        assert!(
+            matches!(resultado, Err(LexerError::ErroNumero { ref texto, .. }) if texto == codigo),
+            "{} deveria ser rejeitado, obtido {:?}", codigo, resultado
+        );
+    }
+}
+
+#[test]
+fn test_lexer_comentarios() -> Result<()> {
+    let codigo = "/* bloco /* aninhado */ ainda comentário */\n/// Soma dois números\n///\n//// comum\n// comum\nfaça soma() {}";
+    let tokens = somente_tokens(codigo)?;
+    assert_eq!(tokens, vec![
+        Token::ComentarioDoc("Soma dois números"),
+        Token::ComentarioDoc(""),
+        Token::Faca,
+        Token::Identificador("soma".into()),
+        Token::AbreParentese,
+        Token::FechaParentese,
+        Token::AbreChave,
+        Token::FechaChave,
+        Token::EOF,
+    ]);
+    
+    let erro = tokenizar("pense x /* /* */").unwrap_err();
+    assert!(matches!(
+        erro.downcast_ref::<LexerError>(),
+        Some(LexerError::ComentarioNaoTerminado { linha: 1, coluna: 9, .. })
+    ));
+    Ok(())
+}
+
+fn ids<'a>(nomes: &[&'a str]) -> Vec<Token<'a>> {
+    let mut tokens: Vec<Token> = nomes.iter().map(|n| Token::Identificador((*n).into())).collect();
+    tokens.push(Token::EOF);
+    tokens
+}
+
+#[test]
+fn test_lexer_para_cada() -> Result<()> {
+    assert_eq!(somente_tokens("para  cada")?, vec![Token::ParaCada, Token::EOF]);
+    assert_eq!(somente_tokens("para x")?, ids(&["para", "x"]));
+    assert_eq!(somente_tokens("para cadastro")?, ids(&["para", "cadastro"]));
+    assert_eq!(somente_tokens("para (")?, vec![
+        Token::Identificador("para".into()),
+        Token::AbreParentese,
+        Token::EOF,
+    ]);
+    
+    // Como na gramática, as palavras ficam na mesma linha
+    assert_eq!(somente_tokens("para\ncada x")?, ids(&["para", "cada", "x"]));
+    
+    // O span cobre a frase inteira
+    let tokens = tokenizar("para \t cada")?;
+    assert_eq!((tokens[0].inicio, tokens[0].fim), (0, 11));
+    assert_eq!((tokens[1].linha, tokens[1].coluna), (1, 12));
+    Ok(())
+}
+
+#[test]
+fn test_lexer_quando_der_erro() -> Result<()> {
+    assert_eq!(somente_tokens("quando der erro")?, vec![Token::QuandoDerErro, Token::EOF]);
+    assert_eq!(somente_tokens("quando dar")?, ids(&["quando", "dar"]));
+    assert_eq!(somente_tokens("quando der certo")?, ids(&["quando", "der", "certo"]));
+    assert_eq!(somente_tokens("quando")?, ids(&["quando"]));
+    assert_eq!(somente_tokens("quando der\nerro")?, ids(&["quando", "der", "erro"]));
+    Ok(())
+}
+
+#[test]
+fn test_lexer_falhar_com() -> Result<()> {
+    assert_eq!(somente_tokens("se falhar com erro")?, vec![
+        Token::Se,
+        Token::FalharCom,
+        Token::Identificador("erro".into()),
+        Token::EOF,
+    ]);
+    assert_eq!(somente_tokens("falhar comigo")?, ids(&["falhar", "comigo"]));
+    Ok(())
+}
+
+#[test]
+fn test_lexer_e_igual_a() -> Result<()> {
+    assert_eq!(somente_tokens("x é igual a 1")?, vec![
+        Token::Identificador("x".into()),
+        Token::IgualA,
+        Token::InteiroLiteral(1),
+        Token::EOF,
+    ]);
+    assert_eq!(somente_tokens("é igual abc")?, ids(&["é", "igual", "abc"]));
+    assert_eq!(somente_tokens("é igual")?, ids(&["é", "igual"]));
+    // "e" (o operador lógico) não é "é", e não deve consumir a palavra seguinte
+    let mut esperado = vec![Token::E];
+    esperado.extend(ids(&["igual", "a"]));
+    assert_eq!(somente_tokens("e igual a")?, esperado);
+    Ok(())
+}
+
+#[test]
+fn test_lexer_identificadores_unicode() -> Result<()> {
+    assert_eq!(
+        somente_tokens("ação Índice über São_Paulo ñandú _x1")?,
+        ids(&["ação", "Índice", "über", "São_Paulo", "ñandú", "_x1"])
+    );
+    
+    // `÷` e `×` ficam dentro do antigo intervalo 'á'..='ú', mas não são letras
+    assert!(matches!(
+        Lexer::new("÷").proximo_token(),
+        Err(LexerError::CaractereInesperado { caractere: '÷', .. })
+    ));
+    assert!(tokenizar("x × y").is_err());
+    
+    // Acento combinante (u + U+0301) é normalizado para a forma pré-composta
+    let decomposto = "me\u{301}dia";
+    let tokens = tokenizar(decomposto)?;
+    assert_eq!(tokens[0].token, Token::Identificador("média".into()));
+    assert_eq!(tokens[0].fim, decomposto.len());
+    
+    // Palavras-chave também são reconhecidas na forma decomposta
+    assert_eq!(somente_tokens("fac\u{327}a")?, vec![Token::Faca, Token::EOF]);
+    Ok(())
+}
+
+#[test]
+fn test_identificadores_iguais_no_lexer_e_na_gramatica() {
+    use pbrlang::parser::{PBRParser, Rule};
+    use pest::Parser;
+    
+    for nome in ["ação", "Índice", "über", "Ç", "_", "x1", "São_Paulo", "me\u{301}dia"] {
+        let tokens = tokenizar(nome).expect("lexer deveria aceitar o identificador");
+        assert!(matches!(tokens[0].token, Token::Identificador(_)), "{}", nome);
+        assert_eq!(tokens[0].fim, nome.len(), "{}", nome);
+        
+        let pares = PBRParser::parse(Rule::identificador, nome).expect("gramática deveria aceitar");
+        assert_eq!(pares.as_str(), nome);
+    }
+    
+    for invalido in ["÷", "×", "1abc"] {
+        assert!(PBRParser::parse(Rule::identificador, invalido).is_err(), "{}", invalido);
+    }
+}
+
+#[test]
+fn test_lexer_operadores() -> Result<()> {
+    let tokens = somente_tokens("a % b resto c == d != !x e não f ou g em h -> [i].j")?;
+    assert_eq!(tokens, vec![
+        Token::Identificador("a".into()), Token::Resto,
+        Token::Identificador("b".into()), Token::Resto,
+        Token::Identificador("c".into()), Token::IgualIgual,
+        Token::Identificador("d".into()), Token::Diferente,
+        Token::Nao, Token::Identificador("x".into()), Token::E,
+        Token::Nao, Token::Identificador("f".into()), Token::Ou,
+        Token::Identificador("g".into()), Token::Em,
+        Token::Identificador("h".into()), Token::Seta,
+        Token::AbreColchete, Token::Identificador("i".into()), Token::FechaColchete,
+        Token::Ponto, Token::Identificador("j".into()),
+        Token::EOF,
+    ]);
+    
+    // `..` nas fatias não faz parte do número
+    let tokens = somente_tokens("l[1..3] l[..n]")?;
+    assert_eq!(tokens, vec![
+        Token::Identificador("l".into()), Token::AbreColchete, Token::InteiroLiteral(1),
+        Token::Intervalo, Token::InteiroLiteral(3), Token::FechaColchete,
+        Token::Identificador("l".into()), Token::AbreColchete, Token::Intervalo,
+        Token::Identificador("n".into()), Token::FechaColchete,
+        Token::EOF,
+    ]);
+    
+    let tokens = somente_tokens("x += 1; x -= 1; x *= 2; x /= 2; x %= 3; x = -1")?;
+    let operadores: Vec<Token> = tokens.into_iter()
+        .filter(|t| !matches!(t, Token::Identificador(_) | Token::InteiroLiteral(_) | Token::PontoVirgula))
+        .collect();
+    assert_eq!(operadores, vec![
+        Token::MaisIgual, Token::MenosIgual, Token::VezesIgual, Token::DivididoIgual,
+        Token::RestoIgual, Token::Igual, Token::Menos, Token::EOF,
+    ]);
+    Ok(())
+}
+
+#[test]
+fn test_lexer_recuperacao_relata_todos_os_erros() {
+    let codigo = "pense x = 1 @ 2;\nmostre \"a\\qb\";\npense y = 12abc;\nfim # /* aberto";
+    let (tokens, erros) = tokenizar_com_recuperacao(codigo);
+    
+    let trechos: Vec<&str> = erros.iter().map(|e| &codigo[e.intervalo()]).collect();
+    assert_eq!(trechos, vec!["@", "\\q", "12abc", "#", "/* aberto"]);
+    assert_eq!(erros[1].linha_coluna(), (2, 10));
+    
+    // Os tokens ao redor dos erros continuam sendo produzidos
+    let tokens: Vec<Token> = tokens.into_iter().map(|t| t.token).collect();
+    assert_eq!(&tokens[..6], &[
+        Token::Pense,
+        Token::Identificador("x".into()),
+        Token::Igual,
+        Token::InteiroLiteral(1),
+        Token::Erro,
+        Token::InteiroLiteral(2),
+    ]);
+    // O texto com escape inválido é mantido, com U+FFFD no lugar do escape
+    assert!(tokens.contains(&Token::TextoLiteral("a\u{fffd}b".into())));
+    assert_eq!(tokens.iter().filter(|t| **t == Token::Erro).count(), 4);
+    assert_eq!(tokens.last(), Some(&Token::EOF));
+}
+
+#[test]
+fn test_lexer_recuperacao_texto_nao_terminado() {
+    let (tokens, erros) = tokenizar_com_recuperacao("mostre \"a ${ b");
+    
+    assert!(matches!(erros.as_slice(), [LexerError::StringNaoTerminada { linha: 1, coluna: 8, .. }]));
+    let tokens: Vec<Token> = tokens.into_iter().map(|t| t.token).collect();
+    assert_eq!(tokens, vec![
+        Token::Mostre,
+        Token::TextoInicio("a ".into()),
+        Token::Identificador("b".into()),
+        Token::Erro,
+        Token::EOF,
+    ]);
+    
+    // Sem erros, o resultado é o mesmo de `tokenizar`
+    let codigo = "pense x = \"ok\";";
+    assert_eq!(tokenizar_com_recuperacao(codigo), (tokenizar(codigo).unwrap(), vec![]));
+}
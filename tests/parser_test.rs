@@ -1,7 +1,7 @@
 use anyhow::Result;
 use pbrlang::{
-    ast::{Declaracao, Expressao, Tipo},
-    parser::analisar_codigo,
+    ast::{Assinatura, Declaracao, Expressao, Operador, Padrao, ParteTexto, Tipo},
+    parser::{analisar_codigo, analisar_com_recuperacao, ErroSintatico},
 };
 use std::fs;
 use std::path::Path;
@@ -67,11 +67,32 @@ fn test_parser_string_interpolation() -> Result<()> {
     // Verificar se é uma declaração de variável com interpolação
     if let Some(Declaracao::Variavel { nome, valor, .. }) = programa.declaracoes.first() {
         assert_eq!(nome, "msg");
-        assert!(matches!(valor, Some(Expressao::TextoLiteral(_))));
+        assert_eq!(valor, &Some(Expressao::TextoInterpolado(vec![
+            ParteTexto::Texto("Olá, ".to_string()),
+            ParteTexto::Expressao(Expressao::Identificador("nome".to_string())),
+            ParteTexto::Texto("!".to_string()),
+        ])));
     } else {
         panic!("Esperava uma declaração de variável");
     }
     
+    // Expressões, espaços, escapes e textos aninhados dentro de `${...}`
+    let programa = analisar_codigo(r#"mostre "${ a + 1 }\t${"x${b}"}\${c}";"#)?;
+    let Some(Declaracao::Mostrar(Expressao::TextoInterpolado(partes))) = programa.declaracoes.first() else {
+        panic!("Esperava um texto interpolado");
+    };
+    assert!(matches!(&partes[0], ParteTexto::Expressao(Expressao::Operacao { operador: Operador::Soma, .. })));
+    assert_eq!(partes[1], ParteTexto::Texto("\t".to_string()));
+    assert!(matches!(&partes[2], ParteTexto::Expressao(Expressao::TextoInterpolado(_))));
+    assert_eq!(partes[3], ParteTexto::Texto("${c}".to_string()));
+    
+    // Sem `${`, o texto continua simples
+    let programa = analisar_codigo(r#"mostre "custa $5 {ok}";"#)?;
+    assert!(matches!(
+        programa.declaracoes.first(),
+        Some(Declaracao::Mostrar(Expressao::TextoLiteral(texto))) if texto == "custa $5 {ok}"
+    ));
+    
     Ok(())
 }
 
@@ -148,3 +169,785 @@ fn test_parser_operadores() -> Result<()> {
     
     Ok(())
 }
+
+#[test]
+fn test_parser_documentacao_em_funcoes() -> Result<()> {
+    let codigo = "/// Mostra uma saudação\n/// para o usuário\nfaça saudar() {\n    mostre \"Olá\"\n}\n";
+    let programa = pbrlang::parser::analisar(pbrlang::tokenizar(codigo)?)?;
+    
+    if let Some(Declaracao::Funcao { documentacao, .. }) = programa.declaracoes.first() {
+        assert_eq!(documentacao.as_deref(), Some("Mostra uma saudação\npara o usuário"));
+    } else {
+        panic!("Esperava uma declaração de função");
+    }
+    
+    Ok(())
+}
+
+#[test]
+fn test_parser_atribuicao_composta() -> Result<()> {
+    let esperado = |operador| Declaracao::Expressao(Expressao::Atribuicao {
+        nome: "total".to_string(),
+        valor: Box::new(Expressao::Operacao {
+            operador,
+            esquerda: Box::new(Expressao::Identificador("total".to_string())),
+            direita: Box::new(Expressao::InteiroLiteral(2)),
+        }),
+    });
+    
+    for (codigo, operador) in [
+        ("total += 2;", Operador::Soma),
+        ("total -= 2;", Operador::Subtracao),
+        ("total *= 2;", Operador::Multiplicacao),
+        ("total /= 2;", Operador::Divisao),
+        ("total %= 2;", Operador::Resto),
+    ] {
+        // O parser baseado em tokens e a gramática produzem a mesma AST
+        let por_tokens = pbrlang::parser::analisar(pbrlang::tokenizar(codigo)?)?;
+        let por_gramatica = analisar_codigo(codigo)?;
+        
+        assert_eq!(por_tokens.declaracoes, vec![esperado(operador.clone())], "{}", codigo);
+        assert_eq!(por_gramatica.declaracoes, vec![esperado(operador)], "{}", codigo);
+    }
+    
+    Ok(())
+}
+
+#[test]
+fn test_parser_legado_igual_a_gramatica() -> Result<()> {
+    // Subconjunto que o parser baseado em tokens entende
+    let codigo = r#"
+        /// Cumprimenta alguém
+        faça saudar(nome: texto, vezes: inteiro) {
+            mostre "Olá, " + nome;
+            total = vezes;
+            volte 1;
+        }
+        
+        saudar("Ana", 2);
+        mostre "fim" + "!";
+        total *= 3;
+    "#;
+    
+    let por_tokens = pbrlang::parser::analisar(pbrlang::tokenizar(codigo)?)?;
+    let por_gramatica = analisar_codigo(codigo)?;
+    
+    assert_eq!(por_tokens.declaracoes, por_gramatica.declaracoes);
+    assert_eq!(por_gramatica.declaracoes.len(), 4);
+    
+    Ok(())
+}
+
+/// Analisa um código inválido e devolve o erro de sintaxe
+fn erro_sintatico(codigo: &str) -> ErroSintatico {
+    // Como na CLI, os símbolos esperados vêm das tentativas do pest
+    pest::set_error_detail(true);
+    let erro = analisar_codigo(codigo).expect_err("o código deveria ser inválido");
+    erro.downcast::<ErroSintatico>().expect("deveria ser um erro de sintaxe")
+}
+
+#[test]
+fn test_parser_erros_sintaticos() {
+    let erro = erro_sintatico("pense x = 1;\nse x > 1 mostre x;");
+    assert_eq!((erro.linha, erro.coluna), (2, 10));
+    assert_eq!(erro.esperado, vec!["`{`"]);
+    assert_eq!(erro.encontrado, "mostre");
+    assert_eq!(erro.trecho, "se x > 1 mostre x;");
+    assert_eq!(
+        erro.to_string(),
+        "linha 2, coluna 10: esperava `{`, encontrou `mostre`\n  |\n2 | se x > 1 mostre x;\n  |          ^^^^^^"
+    );
+    
+    // Nomes amigáveis no lugar das regras da gramática
+    let erro = erro_sintatico("pense x = ;");
+    assert_eq!(erro.esperado, vec!["uma expressão"]);
+    assert_eq!(erro.encontrado, ";");
+    
+    // O valor que falta é apontado mesmo quando a linha seguinte começa uma
+    // declaração
+    let erro = erro_sintatico("pense x =\nmostre 1");
+    assert_eq!(
+        erro.to_string(),
+        "linha 2, coluna 1: esperava uma expressão, encontrou `mostre`\n  |\n2 | mostre 1\n  | ^^^^^^"
+    );
+    let (_, erros) = analisar_com_recuperacao("pense x =\nmostre 1");
+    assert_eq!(erros, vec![erro]);
+    
+    let erro = erro_sintatico("faça f(a) { }");
+    assert_eq!(erro.esperado, vec!["`:`"]);
+    assert_eq!(erro.encontrado, ")");
+    
+    // Sem `;`, a próxima declaração precisa estar em outra linha
+    let erro = erro_sintatico("pense x = 1 mostre x");
+    assert_eq!(erro.esperado, vec!["`;`", "uma quebra de linha"]);
+    assert_eq!((erro.linha, erro.coluna), (1, 13));
+    assert_eq!(erro.encontrado, "mostre");
+    
+    let erro = erro_sintatico("pense x = (1 + 2;");
+    assert_eq!(erro.esperado, vec!["`)`"]);
+    
+    // Números fora dos limites apontam para o literal
+    let erro = erro_sintatico("pense a = 1\npense x = 99999999999999999999");
+    assert_eq!((erro.linha, erro.coluna), (2, 11));
+    assert_eq!(erro.esperado, vec!["um número dentro dos limites"]);
+    assert_eq!(erro.encontrado, "99999999999999999999");
+    assert_eq!(erro.trecho, "pense x = 99999999999999999999");
+    
+    let (_, erros) = analisar_com_recuperacao("pense a = 1\nmostre 2 + 1e999");
+    assert_eq!(erros.iter().map(|e| (e.linha, e.coluna)).collect::<Vec<_>>(), vec![(2, 12)]);
+    assert_eq!(erros[0].trecho, "mostre 2 + 1e999");
+    
+    // Um número não pode continuar com letras ou `_`
+    for (codigo, numero) in [("pense x = 1_", "1_"), ("pense x = 12abc", "12abc"), ("mostre 0xFG + 1", "0xFG"), ("mostre 1e", "1e")] {
+        let erro = erro_sintatico(codigo);
+        assert_eq!(erro.esperado, vec!["um número válido"], "{}", codigo);
+        assert_eq!(erro.encontrado, numero);
+        assert_eq!(erro.coluna, codigo.find(numero).unwrap() + 1);
+    }
+    
+    // Fim do arquivo
+    let erro = erro_sintatico("faça f() {");
+    assert_eq!(erro.encontrado, "");
+    assert!(erro.esperado.contains(&"`}`".to_string()));
+    assert!(erro.to_string().contains("encontrou o fim do arquivo"));
+}
+
+#[test]
+fn test_parser_legado_erros_com_posicao() -> Result<()> {
+    let erro = pbrlang::parser::analisar(pbrlang::tokenizar("faça f(a texto) {}")?)
+        .expect_err("o código deveria ser inválido")
+        .downcast::<ErroSintatico>()
+        .expect("deveria ser um erro de sintaxe");
+    
+    assert_eq!((erro.linha, erro.coluna), (1, 10));
+    assert_eq!(erro.esperado, vec!["`:`"]);
+    assert_eq!(erro.encontrado, "texto");
+    Ok(())
+}
+
+#[test]
+fn test_parser_recuperacao_de_erros() {
+    let codigo = r#"pense x = ;
+pense y = 2;
+se y > 1 mostre y;
+faça f() {
+    mostre 1 mostre 3
+    pense z = ;
+    mostre 2;
+}
+modelo Pessoa { nome: texto idade: inteiro }
+mostre y;"#;
+    
+    let (programa, erros) = analisar_com_recuperacao(codigo);
+    
+    // Cada declaração com erro vira um marcador, e a análise continua
+    assert!(matches!(programa.declaracoes.as_slice(), [
+        Declaracao::Erro,
+        Declaracao::Variavel { .. },
+        Declaracao::Erro,
+        Declaracao::Mostrar(_),
+        Declaracao::Erro,
+        Declaracao::Erro,
+        Declaracao::Mostrar(_),
+    ]));
+    
+    // Os erros dentro do corpo da função também são encontrados
+    let posicoes: Vec<(usize, usize)> = erros.iter().map(|e| (e.linha, e.coluna)).collect();
+    assert_eq!(posicoes, vec![(1, 11), (3, 10), (5, 14), (6, 15), (9, 29)]);
+    assert_eq!(erros[0].to_string(), analisar_codigo(codigo).unwrap_err().to_string());
+    
+    // Sem erros, o resultado é o mesmo da análise normal
+    let codigo = fs::read_to_string("examples/completo.pbr").unwrap();
+    let (programa, erros) = analisar_com_recuperacao(&codigo);
+    assert!(erros.is_empty());
+    assert_eq!(programa.declaracoes, analisar_codigo(&codigo).unwrap().declaracoes);
+}
+
+#[test]
+fn test_parser_indices_e_fatias() -> Result<()> {
+    let expressao = |codigo: &str| -> Result<Expressao> {
+        match analisar_codigo(&format!("mostre {};", codigo))?.declaracoes.into_iter().next() {
+            Some(Declaracao::Mostrar(expr)) => Ok(expr),
+            outra => panic!("Esperava mostre, encontrou {:?}", outra),
+        }
+    };
+    let id = |nome: &str| Box::new(Expressao::Identificador(nome.to_string()));
+    
+    assert_eq!(expressao("lista[0]")?, Expressao::Indice {
+        alvo: id("lista"),
+        indice: Box::new(Expressao::InteiroLiteral(0)),
+    });
+    
+    // Índices encadeados são aplicados da esquerda para a direita
+    assert_eq!(expressao("m[i][j + 1]")?, Expressao::Indice {
+        alvo: Box::new(Expressao::Indice { alvo: id("m"), indice: id("i") }),
+        indice: Box::new(Expressao::Operacao {
+            operador: Operador::Soma,
+            esquerda: id("j"),
+            direita: Box::new(Expressao::InteiroLiteral(1)),
+        }),
+    });
+    
+    assert!(matches!(expressao(r#"pessoa["nome"]"#)?, Expressao::Indice { indice, .. }
+        if *indice == Expressao::TextoLiteral("nome".to_string())));
+    
+    // Fatias com e sem limites
+    assert_eq!(expressao("l[1..3]")?, Expressao::Fatia {
+        alvo: id("l"),
+        inicio: Some(Box::new(Expressao::InteiroLiteral(1))),
+        fim: Some(Box::new(Expressao::InteiroLiteral(3))),
+    });
+    assert_eq!(expressao("l[..n]")?, Expressao::Fatia { alvo: id("l"), inicio: None, fim: Some(id("n")) });
+    assert_eq!(expressao("l[n..]")?, Expressao::Fatia { alvo: id("l"), inicio: Some(id("n")), fim: None });
+    assert_eq!(expressao("[1, 2][..]")?, Expressao::Fatia {
+        alvo: Box::new(Expressao::ListaLiteral(vec![Expressao::InteiroLiteral(1), Expressao::InteiroLiteral(2)])),
+        inicio: None,
+        fim: None,
+    });
+    
+    Ok(())
+}
+
+#[test]
+fn test_parser_cadeias_de_sufixos() -> Result<()> {
+    let expressao = |codigo: &str| -> Result<Expressao> {
+        match analisar_codigo(&format!("mostre {};", codigo))?.declaracoes.into_iter().next() {
+            Some(Declaracao::Mostrar(expr)) => Ok(expr),
+            outra => panic!("Esperava mostre, encontrou {:?}", outra),
+        }
+    };
+    let id = |nome: &str| Box::new(Expressao::Identificador(nome.to_string()));
+    let membro = |objeto: Box<Expressao>, membro: &str| Expressao::AcessoMembro {
+        objeto,
+        membro: membro.to_string(),
+    };
+    
+    assert_eq!(
+        expressao("pessoa.endereco.cidade")?,
+        membro(Box::new(membro(id("pessoa"), "endereco")), "cidade")
+    );
+    
+    assert_eq!(expressao("t.maiusculas(x)")?, Expressao::ChamadaMetodo {
+        objeto: id("t"),
+        metodo: "maiusculas".to_string(),
+        argumentos: vec![Expressao::Identificador("x".to_string())],
+    });
+    
+    // O resultado de uma chamada pode ser acessado, chamado ou indexado
+    let obter = Box::new(Expressao::Chamada { nome: "obter".to_string(), argumentos: vec![] });
+    assert_eq!(expressao("obter().nome")?, membro(obter.clone(), "nome"));
+    assert_eq!(expressao("obter()(1)")?, Expressao::ChamadaExpressao {
+        funcao: obter,
+        argumentos: vec![Expressao::InteiroLiteral(1)],
+    });
+    assert_eq!(expressao("l[0].m()")?, Expressao::ChamadaMetodo {
+        objeto: Box::new(Expressao::Indice { alvo: id("l"), indice: Box::new(Expressao::InteiroLiteral(0)) }),
+        metodo: "m".to_string(),
+        argumentos: vec![],
+    });
+    
+    // Atribuição ao fim de uma cadeia de membros
+    let programa = analisar_codigo("a.b.c = 1;")?;
+    assert!(matches!(&programa.declaracoes[0], Declaracao::Expressao(Expressao::AtribuicaoMembro { objeto, membro, .. })
+        if membro == "c" && **objeto == Expressao::AcessoMembro { objeto: id("a"), membro: "b".to_string() }));
+    
+    Ok(())
+}
+
+/// Forma compacta de uma expressão, para comparar árvores: `(+ a (* b c))`
+fn arvore(expressao: &Expressao) -> String {
+    match expressao {
+        Expressao::Identificador(nome) => nome.clone(),
+        Expressao::InteiroLiteral(n) => n.to_string(),
+        Expressao::LogicoLiteral(b) => b.to_string(),
+        Expressao::Operacao { operador, esquerda, direita } => {
+            format!("({} {} {})", simbolo(operador), arvore(esquerda), arvore(direita))
+        },
+        Expressao::Comparacao { operandos, operadores } => {
+            let mut cadeia = arvore(&operandos[0]);
+            for (operador, operando) in operadores.iter().zip(&operandos[1..]) {
+                cadeia = format!("{} {} {}", cadeia, simbolo(operador), arvore(operando));
+            }
+            format!("(cadeia {})", cadeia)
+        },
+        Expressao::Negacao { expressao } => format!("(não {})", arvore(expressao)),
+        Expressao::Negativo { expressao } => format!("(neg {})", arvore(expressao)),
+        Expressao::AcessoMembro { objeto, membro } => format!("(. {} {})", arvore(objeto), membro),
+        Expressao::Chamada { nome, argumentos } => {
+            let argumentos: Vec<String> = argumentos.iter().map(arvore).collect();
+            format!("({} {})", nome, argumentos.join(" "))
+        },
+        Expressao::Atribuicao { nome, valor } => format!("(= {} {})", nome, arvore(valor)),
+        outra => panic!("Expressão sem forma compacta: {:?}", outra),
+    }
+}
+
+fn simbolo(operador: &Operador) -> &'static str {
+    match operador {
+        Operador::Soma => "+",
+        Operador::Subtracao => "-",
+        Operador::Multiplicacao => "*",
+        Operador::Divisao => "/",
+        Operador::Resto => "%",
+        Operador::Igual => "==",
+        Operador::Diferente => "!=",
+        Operador::Maior => ">",
+        Operador::Menor => "<",
+        Operador::MaiorIgual => ">=",
+        Operador::MenorIgual => "<=",
+        Operador::Contem => "em",
+        Operador::E => "e",
+        Operador::Ou => "ou",
+    }
+}
+
+#[test]
+fn test_parser_precedencia_de_operadores() -> Result<()> {
+    let casos = [
+        // Aritmética: `*`, `/` e `resto` antes de `+` e `-`, sempre à esquerda
+        ("1 + 2 * 3", "(+ 1 (* 2 3))"),
+        ("1 * 2 + 3", "(+ (* 1 2) 3)"),
+        ("10 - 4 - 3", "(- (- 10 4) 3)"),
+        ("8 / 4 / 2", "(/ (/ 8 4) 2)"),
+        ("7 resto 3 * 2", "(* (% 7 3) 2)"),
+        ("a + b % c", "(+ a (% b c))"),
+        ("(1 + 2) * 3", "(* (+ 1 2) 3)"),
+        // Sinal e negação ligam mais forte que os binários, mais fraco que os sufixos
+        ("-n", "(neg n)"),
+        ("-a * b", "(* (neg a) b)"),
+        ("a - -b", "(- a (neg b))"),
+        ("-p.x", "(neg (. p x))"),
+        ("--n", "(neg (neg n))"),
+        ("não a e b", "(e (não a) b)"),
+        ("!a ou não b", "(ou (não a) (não b))"),
+        ("não f(x)", "(não (f x))"),
+        // Comparações abaixo da aritmética, acima de `e` e `ou`
+        ("a + 1 > b * 2", "(> (+ a 1) (* b 2))"),
+        ("a >= b", "(>= a b)"),
+        ("a <= b", "(<= a b)"),
+        ("a é igual a b", "(== a b)"),
+        ("a != b + 1", "(!= a (+ b 1))"),
+        ("x em l e y", "(e (em x l) y)"),
+        ("a > b e b < c", "(e (> a b) (< b c))"),
+        ("a ou b e c", "(ou a (e b c))"),
+        ("a e b ou c e d", "(ou (e a b) (e c d))"),
+        // Comparações de ordem encadeadas valem para cada par vizinho; as
+        // de igualdade comparam o resultado
+        ("a < b < c", "(cadeia a < b < c)"),
+        ("0 <= i < n == verdadeiro", "(== (cadeia 0 <= i < n) true)"),
+        ("a > f(x) >= b + 1", "(cadeia a > (f x) >= (+ b 1))"),
+        ("a == b == c", "(== (== a b) c)"),
+        ("a < b == c < d", "(< (== (< a b) c) d)"),
+        ("(a < b) == c", "(== (< a b) c)"),
+        ("(a < b) < c", "(< (< a b) c)"),
+        // Atribuição tem a menor precedência e associa à direita
+        ("x = y = 1 + 2", "(= x (= y (+ 1 2)))"),
+        // Palavras-chave só são operadores quando inteiras
+        ("estado e ouro", "(e estado ouro)"),
+        ("emprego em empresas", "(em emprego empresas)"),
+        ("restante resto restos", "(% restante restos)"),
+        ("nãoLido ou não lido", "(ou nãoLido (não lido))"),
+    ];
+    
+    for (codigo, esperado) in casos {
+        let programa = analisar_codigo(&format!("{};", codigo))?;
+        match programa.declaracoes.as_slice() {
+            [Declaracao::Expressao(expressao)] => assert_eq!(arvore(expressao), esperado, "{}", codigo),
+            outras => panic!("{}: esperava uma expressão, encontrou {:?}", codigo, outras),
+        }
+    }
+    
+    // O sinal vira um nó próprio, sem mudar o literal
+    let programa = analisar_codigo("volte -1;")?;
+    assert_eq!(
+        programa.declaracoes[0],
+        Declaracao::Retorno(Some(Expressao::Negativo {
+            expressao: Box::new(Expressao::InteiroLiteral(1)),
+        }))
+    );
+    
+    Ok(())
+}
+
+#[test]
+fn test_parser_senao_se_e_escolha() -> Result<()> {
+    let codigo = r#"
+        se n < 0 { mostre "negativo"; } senão se n == 0 { mostre "zero"; } senão { mostre "positivo"; }
+        escolha n {
+            caso 1, 2 { mostre "pouco"; }
+            caso "x" { }
+            padrão { mostre "outro"; }
+        }
+        escolha n { }
+    "#;
+    let programa = analisar_codigo(codigo)?;
+    
+    // `senão se` encadeia outra condicional no lugar do bloco
+    match &programa.declaracoes[0] {
+        Declaracao::Se { bloco_senao: Some(senao), .. } => match senao.as_ref() {
+            Declaracao::Se { condicao, bloco_senao: Some(ultimo), .. } => {
+                assert!(matches!(condicao, Expressao::Operacao { operador: Operador::Igual, .. }));
+                assert!(matches!(ultimo.as_ref(), Declaracao::Bloco(_)));
+            },
+            outro => panic!("Esperava `senão se`, encontrou {:?}", outro),
+        },
+        outra => panic!("Esperava se, encontrou {:?}", outra),
+    }
+    
+    match &programa.declaracoes[1] {
+        Declaracao::Escolha { valor, casos, padrao } => {
+            assert_eq!(*valor, Expressao::Identificador("n".to_string()));
+            assert_eq!(casos.len(), 2);
+            assert_eq!(casos[0].0, vec![
+                Padrao::Valor(Expressao::InteiroLiteral(1)),
+                Padrao::Valor(Expressao::InteiroLiteral(2)),
+            ]);
+            assert_eq!(casos[1].0, vec![Padrao::Valor(Expressao::TextoLiteral("x".to_string()))]);
+            assert!(padrao.is_some());
+        },
+        outra => panic!("Esperava escolha, encontrou {:?}", outra),
+    }
+    
+    assert!(matches!(&programa.declaracoes[2], Declaracao::Escolha { casos, padrao: None, .. } if casos.is_empty()));
+    
+    // O padrão fica depois dos casos
+    let erro = analisar_codigo("escolha n { padrão { } caso 1 { } }")
+        .expect_err("o código deveria ser inválido");
+    assert!(erro.downcast_ref::<ErroSintatico>().is_some());
+    
+    Ok(())
+}
+
+#[test]
+fn test_parser_enumeracoes_e_padroes() -> Result<()> {
+    let codigo = r#"
+        /// Formas geométricas
+        público tipo Forma é um de {
+            Circulo(raio: número),
+            Retangulo(largura: número, altura: número)
+            Vazio
+        }
+        escolha forma {
+            caso Circulo(r) { }
+            caso Retangulo(_, a), Vazio { }
+            caso (dobro(x)) { }
+        }
+    "#;
+    let programa = analisar_codigo(codigo)?;
+    
+    match &programa.declaracoes[0] {
+        Declaracao::Enumeracao { nome, variantes, publico, documentacao } => {
+            assert_eq!(nome, "Forma");
+            assert!(*publico);
+            assert_eq!(documentacao.as_deref(), Some("Formas geométricas"));
+            assert_eq!(variantes, &vec![
+                ("Circulo".to_string(), vec![("raio".to_string(), Tipo::Numero)]),
+                ("Retangulo".to_string(), vec![
+                    ("largura".to_string(), Tipo::Numero),
+                    ("altura".to_string(), Tipo::Numero),
+                ]),
+                ("Vazio".to_string(), vec![]),
+            ]);
+        },
+        outra => panic!("Esperava tipo, encontrou {:?}", outra),
+    }
+    
+    let variante = |nome: &str, campos: &[&str]| Padrao::Variante {
+        nome: nome.to_string(),
+        campos: campos.iter().map(|c| c.to_string()).collect(),
+    };
+    match &programa.declaracoes[1] {
+        Declaracao::Escolha { casos, .. } => {
+            assert_eq!(casos[0].0, vec![variante("Circulo", &["r"])]);
+            // Sem parênteses, o nome é um valor; a variante é reconhecida na conversão
+            assert_eq!(casos[1].0, vec![
+                variante("Retangulo", &["_", "a"]),
+                Padrao::Valor(Expressao::Identificador("Vazio".to_string())),
+            ]);
+            // Entre parênteses, a chamada é um valor comparado
+            assert!(matches!(&casos[2].0[..], [Padrao::Valor(Expressao::Chamada { .. })]));
+        },
+        outra => panic!("Esperava escolha, encontrou {:?}", outra),
+    }
+    
+    // Variantes na mesma linha precisam de vírgula
+    let erro = analisar_codigo("tipo F { A(x: inteiro) B }")
+        .expect_err("o código deveria ser inválido");
+    let erro = erro.downcast_ref::<ErroSintatico>().expect("esperava ErroSintatico");
+    assert_eq!((erro.linha, erro.coluna), (1, 24));
+    assert_eq!(erro.esperado, vec!["`,`", "uma quebra de linha"]);
+    
+    Ok(())
+}
+
+#[test]
+fn test_parser_metodos_e_literais_de_modelo() -> Result<()> {
+    let codigo = r#"
+        modelo Pessoa {
+            nome: texto
+            /// Apresenta a pessoa
+            faça apresentar(eu) -> texto { volte eu.nome }
+        }
+        faça Pessoa.renomear(eu, nome: texto) { eu.nome = nome }
+        pense ana = Pessoa { nome: "Ana", idade: 30, }
+        se ana { }
+    "#;
+    let programa = analisar_codigo(codigo)?;
+    
+    match &programa.declaracoes[0] {
+        Declaracao::Modelo { campos, metodos, .. } => {
+            assert_eq!(campos.len(), 1);
+            match &metodos[..] {
+                [Declaracao::Metodo { modelo, nome, parametros, tipo_retorno, documentacao, .. }] => {
+                    assert_eq!((modelo.as_str(), nome.as_str()), ("Pessoa", "apresentar"));
+                    assert!(parametros.is_empty());
+                    assert_eq!(*tipo_retorno, Some(Tipo::Texto));
+                    assert_eq!(documentacao.as_deref(), Some("Apresenta a pessoa"));
+                },
+                outros => panic!("Esperava um método, encontrou {:?}", outros),
+            }
+        },
+        outra => panic!("Esperava modelo, encontrou {:?}", outra),
+    }
+    
+    match &programa.declaracoes[1] {
+        Declaracao::Metodo { modelo, nome, parametros, corpo, .. } => {
+            assert_eq!((modelo.as_str(), nome.as_str()), ("Pessoa", "renomear"));
+            assert_eq!(parametros, &vec![("nome".to_string(), Tipo::Texto)]);
+            assert!(matches!(corpo.as_ref(), Declaracao::Bloco(corpo)
+                if matches!(&corpo[..], [Declaracao::Expressao(Expressao::AtribuicaoMembro { objeto, .. })]
+                    if **objeto == Expressao::Eu)));
+        },
+        outra => panic!("Esperava método, encontrou {:?}", outra),
+    }
+    
+    assert_eq!(programa.declaracoes[2], Declaracao::Variavel {
+        nome: "ana".to_string(),
+        tipo: None,
+        valor: Some(Expressao::LiteralModelo {
+            modelo: "Pessoa".to_string(),
+            campos: vec![
+                ("nome".to_string(), Expressao::TextoLiteral("Ana".to_string())),
+                ("idade".to_string(), Expressao::InteiroLiteral(30)),
+            ],
+        }),
+        publico: false,
+    });
+    
+    // Sem campos, `ana { }` é a condição e um bloco vazio
+    assert!(matches!(&programa.declaracoes[3], Declaracao::Se { condicao: Expressao::Identificador(_), .. }));
+    
+    // `eu` é reservado e só aparece como receptor
+    assert!(analisar_codigo("pense eu = 1").is_err());
+    
+    Ok(())
+}
+
+#[test]
+fn test_parser_contratos() -> Result<()> {
+    let codigo = r#"
+        /// Algo com área
+        contrato Forma {
+            faça area(eu) -> número
+            faça crescer(fator: número);
+        }
+        modelo Quadrado cumpre Forma, Nomeado { lado: número }
+    "#;
+    let programa = analisar_codigo(codigo)?;
+    
+    assert_eq!(programa.declaracoes[0], Declaracao::Contrato {
+        nome: "Forma".to_string(),
+        metodos: vec![
+            Assinatura {
+                nome: "area".to_string(),
+                parametros: vec![],
+                tipo_retorno: Some(Tipo::Numero),
+                documentacao: None,
+            },
+            Assinatura {
+                nome: "crescer".to_string(),
+                parametros: vec![("fator".to_string(), Tipo::Numero)],
+                tipo_retorno: None,
+                documentacao: None,
+            },
+        ],
+        publico: false,
+        documentacao: Some("Algo com área".to_string()),
+    });
+    assert!(matches!(&programa.declaracoes[1], Declaracao::Modelo { contratos, .. }
+        if *contratos == vec!["Forma".to_string(), "Nomeado".to_string()]));
+    
+    Ok(())
+}
+
+#[test]
+fn test_parser_genericos() -> Result<()> {
+    let codigo = r#"
+        modelo Caixa<T> { valor: T }
+        faça agrupar<T>(itens: lista de T, nomes: mapa de T, pares: mapa de inteiro para lista de T) -> Caixa<T> { }
+        pense vazia: lista de texto
+    "#;
+    let programa = analisar_codigo(codigo)?;
+    
+    let t = || Box::new(Tipo::Personalizado("T".to_string()));
+    assert!(matches!(&programa.declaracoes[0], Declaracao::Modelo { genericos, campos, .. }
+        if *genericos == vec!["T".to_string()] && campos[0].1 == *t()));
+    let Declaracao::Funcao { genericos, parametros, tipo_retorno, .. } = &programa.declaracoes[1] else {
+        panic!("esperava uma função: {:?}", programa.declaracoes[1]);
+    };
+    assert_eq!(*genericos, vec!["T".to_string()]);
+    assert_eq!(parametros[0].1, Tipo::Lista(t()));
+    // Sem `para`, as chaves do mapa são textos
+    assert_eq!(parametros[1].1, Tipo::Mapa(Box::new(Tipo::Texto), t()));
+    assert_eq!(parametros[2].1, Tipo::Mapa(Box::new(Tipo::Inteiro), Box::new(Tipo::Lista(t()))));
+    assert_eq!(*tipo_retorno, Some(Tipo::Generico("Caixa".to_string(), vec![*t()])));
+    assert!(matches!(&programa.declaracoes[2], Declaracao::Variavel { tipo: Some(Tipo::Lista(item)), .. }
+        if **item == Tipo::Texto));
+    
+    // `listade` não é o tipo `lista de`
+    assert!(analisar_codigo("pense x: listade texto").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_parser_funcoes_anonimas_e_tipo_funcao() -> Result<()> {
+    let codigo = r#"
+        faça aplicar(f: função(número, texto) -> lógico, g: funcao()) { }
+        pense dobro = faça(x: número) -> número { volte x * 2 }
+        executar(faça() { mostre "oi" })
+    "#;
+    let programa = analisar_codigo(codigo)?;
+    
+    let Declaracao::Funcao { parametros, .. } = &programa.declaracoes[0] else {
+        panic!("esperava uma função: {:?}", programa.declaracoes[0]);
+    };
+    assert_eq!(parametros[0].1, Tipo::Funcao(vec![Tipo::Numero, Tipo::Texto], Some(Box::new(Tipo::Logico))));
+    assert_eq!(parametros[1].1, Tipo::Funcao(vec![], None));
+    assert!(matches!(&programa.declaracoes[1], Declaracao::Variavel {
+        valor: Some(Expressao::FuncaoAnonima { parametros, tipo_retorno: Some(Tipo::Numero), .. }), ..
+    } if *parametros == vec![("x".to_string(), Tipo::Numero)]));
+    assert!(matches!(&programa.declaracoes[2], Declaracao::Expressao(Expressao::Chamada { argumentos, .. })
+        if matches!(argumentos.as_slice(), [Expressao::FuncaoAnonima { tipo_retorno: None, .. }])));
+    Ok(())
+}
+
+#[test]
+fn test_parser_para_cada_em_colecoes_e_com_passo() -> Result<()> {
+    let codigo = r#"
+        para cada i de 10 até 0 passo -2 { }
+        para cada item em lista { mostre item; }
+        para cada (chave, valor) em mapa { }
+    "#;
+    let programa = analisar_codigo(codigo)?;
+    
+    match &programa.declaracoes[0] {
+        Declaracao::ParaCada { variavel, passo, .. } => {
+            assert_eq!(variavel, "i");
+            assert_eq!(*passo, Some(Expressao::Negativo { expressao: Box::new(Expressao::InteiroLiteral(2)) }));
+        },
+        outra => panic!("Esperava para cada com passo, encontrou {:?}", outra),
+    }
+    assert!(matches!(&programa.declaracoes[1], Declaracao::ParaCadaEm { variavel, colecao, .. }
+        if variavel == "item" && *colecao == Expressao::Identificador("lista".to_string())));
+    assert!(matches!(&programa.declaracoes[2], Declaracao::ParaCadaPar { chave, valor, .. }
+        if chave == "chave" && valor == "valor"));
+    
+    // Sem passo, o intervalo continua como antes
+    let programa = analisar_codigo("para cada i de 1 até 3 { }")?;
+    assert!(matches!(&programa.declaracoes[0], Declaracao::ParaCada { passo: None, .. }));
+    
+    // Como no lexer, `para cada` não atravessa quebras de linha
+    assert!(analisar_codigo("para \t cada item em lista { }").is_ok());
+    assert!(analisar_codigo("para\ncada item em lista { }").is_err());
+    
+    Ok(())
+}
+
+#[test]
+fn test_parser_arquivos_de_lib_e_examples() -> Result<()> {
+    // Arquivos que usam recursos que a gramática ainda não tem
+    const PENDENTES: &[(&str, &str)] = &[];
+    
+    let mut analisados = 0;
+    for pasta in ["lib", "examples"] {
+        for entrada in fs::read_dir(pasta)? {
+            let caminho = entrada?.path();
+            if caminho.extension().is_none_or(|extensao| extensao != "pbr") {
+                continue;
+            }
+            
+            let nome = format!("{}/{}", pasta, caminho.file_name().unwrap().to_string_lossy());
+            let resultado = analisar_codigo(&fs::read_to_string(&caminho)?);
+            match PENDENTES.iter().find(|(pendente, _)| *pendente == nome) {
+                Some((_, motivo)) => assert!(resultado.is_err(), "{} já é analisado; tire-o da lista ({})", nome, motivo),
+                None => {
+                    if let Err(erro) = resultado {
+                        panic!("{}: {}", nome, erro);
+                    }
+                },
+            }
+            analisados += 1;
+        }
+    }
+    
+    assert_eq!(analisados, 14);
+    Ok(())
+}
+
+#[test]
+fn test_parser_ponto_e_virgula_opcional() -> Result<()> {
+    let codigo = "pense x = 1\nmostre x\nx += 2; mostre x\nvolte\n";
+    let programa = analisar_codigo(codigo)?;
+    assert!(matches!(programa.declaracoes.as_slice(), [
+        Declaracao::Variavel { .. },
+        Declaracao::Mostrar(_),
+        Declaracao::Expressao(Expressao::Atribuicao { .. }),
+        Declaracao::Mostrar(_),
+        Declaracao::Retorno(None),
+    ]));
+    
+    // A quebra de linha encerra a expressão: a linha seguinte não é uma
+    // chamada nem uma subtração
+    let programa = analisar_codigo("pense a = b\n(c)\npense d = e\n-f")?;
+    assert_eq!(programa.declaracoes.len(), 4);
+    assert_eq!(programa.declaracoes[1], Declaracao::Expressao(Expressao::Identificador("c".to_string())));
+    assert!(matches!(&programa.declaracoes[3], Declaracao::Expressao(Expressao::Negativo { .. })));
+    
+    // Mas continua depois de um operador, dentro de parênteses e colchetes
+    // e antes de um `.`
+    let programa = analisar_codigo("pense a = 1 +\n    2\nf(a,\n  b)\npense l = [\n    1,\n]\nobter()\n    .nome")?;
+    assert_eq!(programa.declaracoes.len(), 4);
+    assert!(matches!(&programa.declaracoes[3], Declaracao::Expressao(Expressao::AcessoMembro { .. })));
+    
+    // Dentro de parênteses e colchetes, a linha seguinte pode começar com um
+    // operador
+    for (quebrada, inteira) in [
+        ("mostre (1\n + 2)", "mostre (1 + 2)"),
+        ("mostre [1\n + 2]", "mostre [1 + 2]"),
+        ("mostre f(a\n * b, c)[i\n - 1]", "mostre f(a * b, c)[i - 1]"),
+        ("mostre {\"a\": 1\n + 2}", "mostre {\"a\": 1 + 2}"),
+    ] {
+        assert_eq!(analisar_codigo(quebrada)?.declaracoes, analisar_codigo(inteira)?.declaracoes, "{}", quebrada);
+    }
+    
+    // O valor de `volte` precisa estar na mesma linha
+    let programa = analisar_codigo("faça f() {\n    volte\n    x\n}")?;
+    match &programa.declaracoes[0] {
+        Declaracao::Funcao { corpo, .. } => assert!(matches!(corpo.as_ref(),
+            Declaracao::Bloco(corpo) if matches!(corpo.as_slice(), [Declaracao::Retorno(None), Declaracao::Expressao(_)]))),
+        outra => panic!("Esperava uma função, encontrou {:?}", outra),
+    }
+    
+    // Campos de modelo também dispensam o `;` quando estão em linhas separadas
+    let programa = analisar_codigo("modelo P {\n    nome: texto\n    idade: inteiro\n}")?;
+    assert!(matches!(&programa.declaracoes[0], Declaracao::Modelo { campos, .. } if campos.len() == 2));
+    
+    // Um comentário de linha pode vir antes da quebra; um de bloco, não
+    // substitui o separador
+    let programa = analisar_codigo("pense x = 1 // um\r\nmostre x /* dois */\nse x { mostre x } mostre x")?;
+    assert_eq!(programa.declaracoes.len(), 4);
+    let erro = erro_sintatico("pense x = 1 /* um */ mostre x");
+    assert_eq!((erro.linha, erro.coluna, erro.encontrado.as_str()), (1, 22, "mostre"));
+    
+    Ok(())
+}
@@ -0,0 +1,438 @@
+use anyhow::{anyhow, Result};
+use pbrlang::analisar_codigo;
+use pbrlang::transpiler::{gerar_codigo_rust, identificador_rust};
+use std::process::Command;
+
+/// Converte o programa, compila o Rust gerado com `rustc` e devolve o que
+/// `principal` escreveu
+fn executar(codigo: &str) -> Result<String> {
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    let pasta = tempfile::tempdir()?;
+    let fonte = pasta.path().join("programa.rs");
+    let executavel = pasta.path().join("programa");
+    std::fs::write(&fonte, format!("{}\nfn main() {{ principal(); }}\n", rust))?;
+    
+    let compilacao = Command::new("rustc")
+        .args(["--edition", "2024", "-A", "warnings", "-o"])
+        .arg(&executavel)
+        .arg(&fonte)
+        .output()?;
+    if !compilacao.status.success() {
+        return Err(anyhow!("{}\n{}", String::from_utf8_lossy(&compilacao.stderr), rust));
+    }
+    let execucao = Command::new(&executavel).output()?;
+    Ok(String::from_utf8(execucao.stdout)?)
+}
+
+#[test]
+fn test_identificadores_rust() {
+    assert_eq!(identificador_rust("idade"), "idade");
+    assert_eq!(identificador_rust("match"), "r#match");
+    assert_eq!(identificador_rust("self"), "self_");
+    
+    // Nomes acentuados viram ASCII com um sufixo estável
+    let numero = identificador_rust("número");
+    assert!(numero.starts_with("numero_"), "{}", numero);
+    assert_eq!(numero, identificador_rust("número"));
+    assert_ne!(numero, identificador_rust("numero"));
+    assert_ne!(numero, identificador_rust("numéro"));
+    assert!(identificador_rust("π").starts_with("u3c0_"));
+    assert!(identificador_rust("Ação").starts_with("Acao_"));
+    
+    for nome in ["número", "Ação", "π", "über"] {
+        assert!(identificador_rust(nome).chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+    }
+}
+
+#[test]
+fn test_texto_interpolado_vira_format() -> Result<()> {
+    let codigo = r#"
+        modelo Ponto { x: inteiro; y: inteiro; }
+        pense p = nova Ponto();
+        pense msg = "{p} = ${p}, ok = ${1 > 0}";
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    
+    assert!(rust.contains(r#"format!("{{p}} = {}, ok = {}", (p).exibir(), ((1i64 > 0i64)).exibir())"#), "{}", rust);
+    assert!(rust.contains(r#"format!("Ponto {{ x: {}, y: {} }}", self.x.exibir(), self.y.exibir())"#), "{}", rust);
+    assert!(rust.contains("impl Exibir for bool"));
+    Ok(())
+}
+
+#[test]
+fn test_indices_com_verificacao_de_limites() -> Result<()> {
+    let codigo = r#"
+        pense l = [1, 2, 3];
+        pense a = l[0];
+        pense b = l[1..];
+        quando der erro { mostre l[5]; } se falhar com e { mostre e; }
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    
+    assert!(rust.contains("let mut a = (l).indexar(0i64);"), "{}", rust);
+    assert!(rust.contains("let mut b = (l).fatiar(Some(1i64), None);"), "{}", rust);
+    // Os índices inválidos viram erros PBRLang, capturados por `quando der erro`
+    assert!(rust.contains("fora dos limites da lista"));
+    assert!(rust.contains("match capturar(|| {"), "{}", rust);
+    Ok(())
+}
+
+#[test]
+fn test_chamadas_de_metodo_e_de_expressao() -> Result<()> {
+    let codigo = r#"
+        pense a = t.maiusculas(x, 1);
+        pense b = obter()(2);
+        pense c = pessoa.endereco.cidade;
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    
+    assert!(rust.contains("let mut a = (t).maiusculas(x, 1i64);"), "{}", rust);
+    assert!(rust.contains("let mut b = (obter())(2i64);"), "{}", rust);
+    assert!(rust.contains("let mut c = pessoa.endereco.cidade;"), "{}", rust);
+    Ok(())
+}
+
+#[test]
+fn test_senao_se_e_escolha_viram_else_if_e_match() -> Result<()> {
+    let codigo = r#"
+        se a { mostre 1; } senão se b { mostre 2; } senão { mostre 3; }
+        escolha n {
+            caso 1, -2 { mostre "inteiro"; }
+            caso 1.5, limite { mostre "outro"; }
+        }
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    
+    assert!(rust.contains("} else if b {"), "{}", rust);
+    assert!(rust.contains("match &n {"), "{}", rust);
+    assert!(rust.contains("1i64 | -2i64 => {"), "{}", rust);
+    // `f64` não pode ser padrão em Rust: a comparação fica numa guarda
+    assert!(rust.contains("valor_escolhido if *valor_escolhido == 1.5 || *valor_escolhido == limite => {"), "{}", rust);
+    assert!(rust.contains("_ => {"), "{}", rust);
+    Ok(())
+}
+
+#[test]
+fn test_para_cada_em_colecoes_e_com_passo() -> Result<()> {
+    let codigo = r#"
+        para cada i de 10 até 1 passo -3 { }
+        para cada x em l { }
+        para cada (k, v) em m { }
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    
+    assert!(rust.contains("for i in intervalo(10i64, 1i64, (-3i64)) {"), "{}", rust);
+    assert!(rust.contains("for x in (l).itens() {"), "{}", rust);
+    assert!(rust.contains("for (k, v) in (m).itens() {"), "{}", rust);
+    Ok(())
+}
+
+#[test]
+fn test_enumeracoes_viram_enum_e_match() -> Result<()> {
+    let codigo = r#"
+        tipo Forma { Circulo(raio: número), Vazio }
+        escolha forma {
+            caso Circulo(r) { mostre r; }
+            caso Vazio { }
+        }
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    
+    assert!(rust.contains("enum Forma {\n    Circulo(f64),\n    Vazio,\n}"), "{}", rust);
+    assert!(rust.contains("use self::Forma::*;"), "{}", rust);
+    assert!(rust.contains("Circulo(r) => {\nlet r = r.clone();"), "{}", rust);
+    assert!(rust.contains("Vazio => {"), "{}", rust);
+    // Todas as variantes foram tratadas: não há braço `_`
+    assert!(!rust.contains("_ => {"), "{}", rust);
+    
+    // Sem `padrão`, faltar uma variante é um erro
+    let incompleta = analisar_codigo("tipo F { A, B, C }\nescolha f { caso A { } }")?;
+    let erro = gerar_codigo_rust(incompleta).expect_err("a escolha deveria ser incompleta");
+    assert!(erro.to_string().contains("não trata `B`, `C`"), "{}", erro);
+    
+    let campos = analisar_codigo("tipo F { A(x: inteiro) }\nescolha f { caso A(x, y) { } }")?;
+    assert!(gerar_codigo_rust(campos).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_metodos_viram_impl_com_self_inferido() -> Result<()> {
+    let codigo = r#"
+        modelo Contador {
+            valor: inteiro
+            faça atual(eu) -> inteiro { volte eu.valor }
+            faça somar(eu, n: inteiro) { eu.valor += n }
+        }
+        faça Contador.zerar(eu) { eu.somar(-eu.atual()) }
+        faça Contador.dobro(eu) -> inteiro { volte eu.atual() * 2 }
+        faça Contador.reiniciar(eu) { pense mesmo = eu
+            mesmo.valor = 0 }
+        pense c = Contador { valor: 1 }
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    
+    assert!(rust.contains("impl Contador {\nfn atual(&self) -> i64 {\nreturn self.valor.clone();"), "{}", rust);
+    assert!(rust.contains("fn somar(&mut self, n: i64) {\nself.valor = (self.valor.clone() + n);"), "{}", rust);
+    // Chamar um método que altera `eu` também o altera; os demais, não
+    assert!(rust.contains("fn zerar(&mut self) {"), "{}", rust);
+    assert!(rust.contains("fn dobro(&self) -> i64 {"), "{}", rust);
+    // Alterar `eu` por outra variável também conta
+    assert!(rust.contains("fn reiniciar(&mut self) {"), "{}", rust);
+    assert!(rust.contains("Contador { valor: (1i64).converter(), }"), "{}", rust);
+    Ok(())
+}
+
+#[test]
+fn test_contratos_viram_traits() -> Result<()> {
+    let codigo = r#"
+        contrato Forma {
+            faça area(eu) -> número
+            faça crescer(eu, fator: número)
+        }
+        modelo Quadrado cumpre Forma {
+            lado: número
+            faça area(eu) -> número { volte eu.lado * eu.lado }
+            faça diagonal(eu) -> número { volte eu.lado }
+        }
+        faça Quadrado.crescer(eu, fator: número) { eu.lado = eu.lado * fator }
+        faça mostrar(f: Forma) { mostre f.area(); }
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    
+    // Uma implementação altera `eu`: o contrato pede `&mut self`
+    assert!(rust.contains("trait Forma {\nfn area(&self) -> f64;\nfn crescer(&mut self, fator: f64);\n}"), "{}", rust);
+    assert!(rust.contains("impl Quadrado {\nfn diagonal(&self) -> f64 {"), "{}", rust);
+    // Os métodos declarados fora do modelo entram no mesmo `impl`
+    assert!(rust.contains("impl Forma for Quadrado {\nfn area(&self) -> f64 {"), "{}", rust);
+    assert!(rust.contains("}\nfn crescer(&mut self, fator: f64) {"), "{}", rust);
+    assert_eq!(rust.matches("fn crescer(&mut self").count(), 2, "{}", rust);
+    assert!(rust.contains("fn mostrar(f: impl Forma) {"), "{}", rust);
+    
+    let sem_metodo = analisar_codigo("contrato C { faça f(eu) }\nmodelo M cumpre C { x: inteiro }")?;
+    let erro = gerar_codigo_rust(sem_metodo).expect_err("o modelo não cumpre o contrato");
+    assert!(erro.to_string().contains("não tem o método `f`"), "{}", erro);
+    
+    let tipos = analisar_codigo("contrato C { faça f(eu, n: inteiro) }\nmodelo M cumpre C { faça f(eu, n: texto) { } }")?;
+    assert!(gerar_codigo_rust(tipos).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_genericos_viram_parametros_de_tipo() -> Result<()> {
+    let codigo = r#"
+        modelo Caixa<T> {
+            valor: T
+            rotulo: texto
+            faça abrir(eu) -> T { volte eu.valor }
+        }
+        faça primeiro<T>(itens: lista de T) -> T { volte itens[0] }
+        faça contar(pares: mapa de texto para inteiro) -> inteiro { volte 0 }
+        pense c = Caixa { valor: 5, rotulo: "cinco" }
+        pense nomes: lista de texto
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    
+    let restricoes = "Clone + PartialEq + std::fmt::Debug + Exibir";
+    assert!(rust.contains("#[derive(Clone, Debug, Default, PartialEq)]\nstruct Caixa<T> {\n    valor: T,"), "{}", rust);
+    assert!(rust.contains(&format!("impl<T: {}> Exibir for Caixa<T> {{", restricoes)), "{}", rust);
+    assert!(rust.contains(&format!("impl<T: {}> Caixa<T> {{\nfn abrir(&self) -> T {{", restricoes)), "{}", rust);
+    assert!(rust.contains(&format!("fn primeiro<T: {}>(itens: Vec<T>) -> T {{", restricoes)), "{}", rust);
+    assert!(rust.contains("fn contar(pares: HashMap<String, i64>) -> i64 {"), "{}", rust);
+    // O campo genérico recebe o valor como está, e nenhum campo falta
+    assert!(rust.contains("Caixa { valor: 5i64, rotulo: (\"cinco\").converter(), }"), "{}", rust);
+    assert!(rust.contains("let mut nomes: Vec<String> = <Vec<String>>::default();"), "{}", rust);
+    Ok(())
+}
+
+#[test]
+fn test_funcoes_como_valores_e_funcoes_anonimas() -> Result<()> {
+    let codigo = r#"
+        faça dobro(x: inteiro) -> inteiro { volte x * 2 }
+        faça aplicar(f: função(inteiro) -> inteiro, x: inteiro) -> inteiro { volte f(x) }
+        faça principal() {
+            pense base = 10
+            pense somar = faça(x: inteiro) -> inteiro { pense y = x + base; volte y }
+            aplicar(somar, aplicar(dobro, 1))
+        }
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    
+    assert!(rust.contains("fn aplicar(f: std::rc::Rc<dyn Fn(i64) -> i64>, x: i64) -> i64 {\nreturn f(x);"), "{}", rust);
+    // Só `base` é de fora da função anônima, e entra nela como cópia
+    assert!(rust.contains("let mut somar = { let base = base.clone(); std::rc::Rc::new(move |x: i64| -> i64 {"), "{}", rust);
+    assert!(rust.contains("}) as std::rc::Rc<dyn Fn(i64) -> i64> };"), "{}", rust);
+    assert!(rust.contains("aplicar(somar.clone(), aplicar(std::rc::Rc::new(dobro) as std::rc::Rc<dyn Fn(i64) -> i64>, 1i64))"), "{}", rust);
+    
+    let altera = analisar_codigo("faça f() { pense total = 0\n pense g = faça(x: inteiro) { total += x } }")?;
+    let erro = gerar_codigo_rust(altera).expect_err("a função anônima altera uma variável de fora");
+    assert!(erro.to_string().contains("não pode alterar `total`"), "{}", erro);
+    Ok(())
+}
+
+#[test]
+fn test_tipos_declarados_e_inteiros_como_numeros() -> Result<()> {
+    let codigo = r#"
+        faça metade(x: número) -> número { volte x / 2 }
+        faça principal() {
+            pense preco: número = 10
+            pense total = preco * 3
+            pense notas: lista de número = [7, 8.5]
+            pense nome: texto = "Ana"
+            preco = 4
+            mostre metade(5)
+            mostre total
+            mostre preco + notas[0]
+            mostre nome
+        }
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    assert!(rust.contains("let mut preco: f64 = 10.0;"), "{}", rust);
+    assert!(rust.contains("let mut notas: Vec<f64> = vec![7.0, 8.5];"), "{}", rust);
+    
+    assert_eq!(executar(codigo)?, "2.5\n30\n11\nAna\n");
+    Ok(())
+}
+
+#[test]
+fn test_mostre_exibe_valores_como_em_pbrlang() -> Result<()> {
+    let codigo = r#"
+        faça principal() {
+            pense nomes = ["Ana", "Bia"]
+            mostre "olá"
+            mostre 1 < 2
+            mostre nomes
+            mostre 2.0 * 3
+            mostre nada
+            mostre "era ${nada}"
+        }
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    assert!(rust.contains("println!(\"{}\", (\"olá\").exibir());"), "{}", rust);
+    // Sozinho, `nada` precisa de um tipo para o Rust
+    assert!(rust.contains("println!(\"{}\", (None::<()>).exibir());"), "{}", rust);
+    
+    assert_eq!(executar(codigo)?, "olá\nverdadeiro\n[Ana, Bia]\n6\nnada\nera nada\n");
+    Ok(())
+}
+
+#[test]
+fn test_comparacoes_encadeadas_avaliam_cada_operando_uma_vez() -> Result<()> {
+    let codigo = r#"
+        faça meio() -> inteiro {
+            mostre "meio"
+            volte 5
+        }
+        faça principal() {
+            mostre 1 < meio() <= 5
+            mostre 5 < 1 < meio()
+            mostre 0 <= 2 < 3.5
+            mostre 1 < 2 == falso
+        }
+    "#;
+    assert_eq!(executar(codigo)?, "meio\nverdadeiro\nfalso\nverdadeiro\nfalso\n");
+    Ok(())
+}
+
+#[test]
+fn test_metodos_so_pedem_mut_self_quando_alteram_eu() -> Result<()> {
+    let codigo = r#"
+        modelo Pilha {
+            itens: lista de inteiro
+            total: inteiro
+            faça vazia(eu) -> lógico { volte eu.tamanho() == 0 }
+            faça tamanho(eu) -> inteiro { volte eu.total }
+            faça empilhar(eu, n: inteiro) { eu.itens.push(n) }
+            faça empilhar_dois(eu, n: inteiro) {
+                pense mesma = eu
+                mesma.empilhar(n)
+                mesma.empilhar(n)
+                mesma.total += 2
+            }
+        }
+        faça principal() {
+            pense p = Pilha { itens: [], total: 0 }
+            mostre p.vazia()
+            p.empilhar_dois(7)
+            mostre p.tamanho()
+        }
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    // `vazia` chama `tamanho`, declarado depois, que também só lê `eu`
+    assert!(rust.contains("fn vazia(&self) -> bool {"), "{}", rust);
+    assert!(rust.contains("fn tamanho(&self) -> i64 {"), "{}", rust);
+    // `push` altera a lista, um campo de `eu`
+    assert!(rust.contains("fn empilhar(&mut self, n: i64) {"), "{}", rust);
+    assert!(rust.contains("fn empilhar_dois(&mut self, n: i64) {"), "{}", rust);
+    
+    assert_eq!(executar(codigo)?, "verdadeiro\n2\n");
+    Ok(())
+}
+
+#[test]
+fn test_biblioteca_padrao_compila() -> Result<()> {
+    // `console.pbr` ainda chama funções de `colecoes.pbr` sem importá-las
+    // e soma números a textos, e fica de fora
+    for modulo in ["arquivo", "colecoes", "numero", "sistema", "texto", "web"] {
+        let codigo = std::fs::read_to_string(format!("lib/{}.pbr", modulo))?;
+        let rust = gerar_codigo_rust(analisar_codigo(&codigo)?)?;
+        let pasta = tempfile::tempdir()?;
+        let fonte = pasta.path().join(format!("{}.rs", modulo));
+        std::fs::write(&fonte, &rust)?;
+        
+        let compilacao = Command::new("rustc")
+            .args(["--edition", "2024", "--crate-type", "lib", "-A", "warnings", "--out-dir"])
+            .arg(pasta.path())
+            .arg(&fonte)
+            .output()?;
+        assert!(compilacao.status.success(), "lib/{}.pbr\n{}", modulo, String::from_utf8_lossy(&compilacao.stderr));
+    }
+    
+    // `coisa` é um parâmetro de tipo implícito de cada função que o usa
+    let colecoes = gerar_codigo_rust(analisar_codigo(&std::fs::read_to_string("lib/colecoes.pbr")?)?)?;
+    assert!(colecoes.contains("fn adicionar<coisa: Clone"), "{}", colecoes);
+    assert!(colecoes.contains("(lista: Vec<coisa>, item: coisa)"), "{}", colecoes);
+    Ok(())
+}
+
+#[test]
+fn test_nomes_seguem_o_escopo_em_que_sao_usados() -> Result<()> {
+    let codigo = r#"
+        contrato Falante {
+            faça falar(eu) -> texto
+        }
+        modelo Gato cumpre Falante {
+            nome: texto
+            faça falar(eu) -> texto { volte "miau" }
+        }
+        faça total() -> inteiro { volte 1 }
+        faça aplicar(f: função(número) -> número, x: número) -> número { volte f(x) }
+        faça apresentar(f: Falante) -> texto { volte f.falar() }
+        faça principal() {
+            pense total = 5
+            mostre total
+            pense dobro = faça(x: número) -> número { volte x * 2 }
+            mostre aplicar(dobro, 1.5)
+            mostre aplicar(dobro, 2)
+            pense somar = faça(x: número) -> número {
+                pense total = 0.5
+                volte x + total
+            }
+            mostre somar(1)
+            mostre apresentar(Gato { nome: "Tom" })
+        }
+    "#;
+    let rust = gerar_codigo_rust(analisar_codigo(codigo)?)?;
+    // Um parâmetro que não guarda uma função é usado como está
+    assert!(rust.contains("return (f).falar();"), "{}", rust);
+    // A variável `total` esconde a função `total`
+    assert!(rust.contains("println!(\"{}\", (total).exibir());"), "{}", rust);
+    // O `total` de dentro da função anônima não é o de fora
+    assert!(rust.contains("let mut somar = { std::rc::Rc::new(move |x: f64|"), "{}", rust);
+    
+    assert_eq!(executar(codigo)?, "5\n3\n4\n1.5\nmiau\n");
+    
+    let eu = analisar_codigo("modelo M { x: inteiro\n faça f(eu) { pense g = faça() { mostre eu.x } } }")?;
+    let erro = gerar_codigo_rust(eu).expect_err("a função anônima usa `eu`");
+    assert!(erro.to_string().contains("não pode usar `eu`"), "{}", erro);
+    Ok(())
+}